@@ -3,14 +3,26 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::WithContractAbi,
-    views::{RootView, View},
+    linera_base_types::{
+        ApplicationId, ChainId, GenericApplicationId, StreamName, StreamUpdate, WithContractAbi,
+    },
+    views::{CustomMapView, RootView, View},
     Contract, ContractRuntime,
 };
 
-use dashboard::{Operation, DashboardEvent};
+use dashboard::{
+    score_bucket, Candle, CandleInterval, CandleSeries, DashboardEvent, DashboardEventType,
+    DashboardInstantiation, DashboardParameters, EventRecord, LatestPrice, LatestScore, Message,
+    Operation, RateSeries, SourceEvent, StreamSubscription, DASHBOARD_STREAM_NAME,
+    MAX_BUCKETS_PER_SERIES,
+};
+use synapsenet_common::{
+    identity_score::{self, IdentityScoreAbi, IdentityScoreEvent, IdentityScoreResponse},
+    price_feed::{self, PriceFeedAbi, PriceFeedEvent, PriceFeedResponse},
+    Price,
+};
 
-use self::state::DashboardState;
+use self::state::{BucketStart, DashboardState, RankedScore};
 
 pub struct DashboardContract {
     state: DashboardState,
@@ -24,9 +36,9 @@ impl WithContractAbi for DashboardContract {
 }
 
 impl Contract for DashboardContract {
-    type Message = ();
-    type Parameters = ();
-    type InstantiationArgument = ();
+    type Message = Message;
+    type Parameters = DashboardParameters;
+    type InstantiationArgument = DashboardInstantiation;
     type EventValue = DashboardEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
@@ -36,24 +48,542 @@ impl Contract for DashboardContract {
         DashboardContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
-        self.runtime.application_parameters();
-        // Chain ID available via self.runtime.chain_id() when needed
+    async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
+        let admin = argument
+            .admin
+            .or_else(|| self.runtime.authenticated_signer());
+        self.state.admin.set(admin);
+        for source in &argument.sources {
+            self.state
+                .sources
+                .insert(source)
+                .expect("Failed to add source");
+        }
+        self.state
+            .created_at
+            .set(self.runtime.system_time().micros());
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         match operation {
-            Operation::ReceiveEvent { event_type, payload } => {
-                let timestamp = self.runtime.system_time().micros();
-                self.state.received_events.get_mut().push(format!("{}: {}", event_type, payload));
-                // Event emission handled by framework via EventValue type
+            Operation::ReceiveEvent {
+                event_type,
+                source_chain_id,
+                source_application_id,
+                payload,
+                emitted_at,
+            } => {
+                self.assert_admin();
+                if !is_json(&payload) {
+                    *self.state.invalid_events.get_mut() += 1;
+                    return;
+                }
+                let event = SourceEvent::from_json(event_type, &payload);
+                self.record_event(
+                    event_type,
+                    source_chain_id,
+                    source_application_id,
+                    payload,
+                    emitted_at,
+                    event.as_ref(),
+                )
+                .await;
+            }
+            Operation::Forward { event, index } => {
+                let source_application_id = self
+                    .runtime
+                    .authenticated_caller_id()
+                    .expect("Events can only be forwarded by applications");
+                let dashboard_chain_id = self.runtime.application_parameters().chain_id;
+                self.runtime
+                    .prepare_message(Message::Event {
+                        source_application_id,
+                        event,
+                        index,
+                    })
+                    .with_authentication()
+                    .send_to(dashboard_chain_id);
+            }
+            Operation::AddSource { application_id } => {
+                self.assert_admin();
+                self.state
+                    .sources
+                    .insert(&application_id)
+                    .expect("Failed to add source");
+            }
+            Operation::RemoveSource { application_id } => {
+                self.assert_admin();
+                self.state
+                    .sources
+                    .remove(&application_id)
+                    .expect("Failed to remove source");
+            }
+            Operation::Subscribe {
+                chain_id,
+                application_id,
+                event_type,
+            } => {
+                self.assert_admin();
+                assert!(
+                    self.is_source(&application_id).await,
+                    "Only accepted sources can be subscribed to"
+                );
+                let stream_name = event_type
+                    .stream_name()
+                    .expect("Only price and score updates are emitted on event streams");
+                self.runtime.subscribe_to_events(
+                    chain_id,
+                    application_id,
+                    StreamName::from(stream_name),
+                );
+                self.state
+                    .subscriptions
+                    .insert(&StreamSubscription {
+                        chain_id,
+                        application_id,
+                        event_type,
+                    })
+                    .expect("Failed to store subscription");
+            }
+            Operation::Unsubscribe {
+                chain_id,
+                application_id,
+                event_type,
+            } => {
+                self.assert_admin();
+                let stream_name = event_type
+                    .stream_name()
+                    .expect("Only price and score updates are emitted on event streams");
+                self.runtime.unsubscribe_from_events(
+                    chain_id,
+                    application_id,
+                    StreamName::from(stream_name),
+                );
+                self.state
+                    .subscriptions
+                    .remove(&StreamSubscription {
+                        chain_id,
+                        application_id,
+                        event_type,
+                    })
+                    .expect("Failed to remove subscription");
             }
         }
     }
 
-    async fn execute_message(&mut self, _message: Self::Message) {}
+    async fn execute_message(&mut self, message: Self::Message) {
+        match message {
+            Message::Event {
+                source_application_id,
+                event,
+                index,
+            } => {
+                if !self.is_source(&source_application_id).await {
+                    *self.state.rejected_messages.get_mut() += 1;
+                    return;
+                }
+                let source_chain_id = self
+                    .runtime
+                    .message_origin_chain_id()
+                    .expect("Incoming message must have an origin chain");
+                let stream = StreamSubscription {
+                    chain_id: source_chain_id,
+                    application_id: source_application_id,
+                    event_type: event.event_type(),
+                };
+                if self.is_new_event(stream, index).await {
+                    self.record_source_event(source_chain_id, source_application_id, event)
+                        .await;
+                }
+            }
+        }
+    }
+
+    async fn process_streams(&mut self, updates: Vec<StreamUpdate>) {
+        for update in updates {
+            let GenericApplicationId::User(application_id) = update.stream_id.application_id else {
+                continue;
+            };
+            let Some(event_type) =
+                DashboardEventType::from_stream_name(&update.stream_id.stream_name.0)
+            else {
+                *self.state.invalid_events.get_mut() += update.new_indices().count() as u64;
+                continue;
+            };
+            if !self.is_source(&application_id).await {
+                *self.state.rejected_messages.get_mut() += update.new_indices().count() as u64;
+                continue;
+            }
+            let stream = StreamSubscription {
+                chain_id: update.chain_id,
+                application_id,
+                event_type,
+            };
+            for index in update.new_indices() {
+                if !self.is_new_event(stream.clone(), index).await {
+                    continue;
+                }
+                let Some(event) = self.read_source_event(&stream, index) else {
+                    *self.state.invalid_events.get_mut() += 1;
+                    continue;
+                };
+                self.record_source_event(update.chain_id, application_id, event)
+                    .await;
+            }
+        }
+    }
 
     async fn store(mut self) {
         self.state.save().await.expect("Failed to save state");
     }
 }
+
+impl DashboardContract {
+    /// Rejects the operation unless it was signed by the admin.
+    fn assert_admin(&mut self) {
+        let signer = self
+            .runtime
+            .authenticated_signer()
+            .expect("Operation must be signed");
+        assert_eq!(
+            self.state.admin.get().as_ref(),
+            Some(&signer),
+            "Only the admin can perform this operation"
+        );
+    }
+
+    /// Whether `sources` contains `application_id`.
+    async fn is_source(&self, application_id: &ApplicationId) -> bool {
+        self.state
+            .sources
+            .contains(application_id)
+            .await
+            .expect("Failed to read sources")
+    }
+
+    /// Whether the event at `index` of `stream` was not recorded yet, in which case it is marked
+    /// as recorded. Events of a stream arrive in order through each of forwarded messages and
+    /// stream updates, but the two may interleave.
+    async fn is_new_event(&mut self, stream: StreamSubscription, index: u32) -> bool {
+        let next_index = self
+            .state
+            .next_event_indices
+            .get_mut_or_default(&stream)
+            .await
+            .expect("Failed to read event indices");
+        if index < *next_index {
+            return false;
+        }
+        *next_index = index + 1;
+        true
+    }
+
+    /// Records an event of a source application on `source_chain_id`.
+    async fn record_source_event(
+        &mut self,
+        source_chain_id: ChainId,
+        source_application_id: ApplicationId,
+        event: SourceEvent,
+    ) {
+        self.record_event(
+            event.event_type(),
+            source_chain_id,
+            Some(source_application_id),
+            event.to_json(),
+            event.emitted_at(),
+            Some(&event),
+        )
+        .await;
+    }
+
+    /// Appends an event to the retained events, evicting old ones as needed, and announces it on
+    /// the dashboard stream. `event` is the decoded payload of price and score updates.
+    async fn record_event(
+        &mut self,
+        event_type: DashboardEventType,
+        source_chain_id: ChainId,
+        source_application_id: Option<ApplicationId>,
+        payload: String,
+        emitted_at: u64,
+        event: Option<&SourceEvent>,
+    ) {
+        let received_at = self.runtime.system_time().micros();
+        self.update_aggregates(event_type, event, received_at).await;
+        let sequence = *self.state.next_sequence.get();
+        self.state.next_sequence.set(sequence + 1);
+        self.state.received_events.push_back(EventRecord {
+            sequence,
+            event_type,
+            source_chain_id,
+            source_application_id,
+            payload,
+            emitted_at,
+            received_at,
+        });
+        self.runtime.emit(
+            StreamName::from(DASHBOARD_STREAM_NAME),
+            &DashboardEvent::EventReceived {
+                sequence,
+                event_type,
+                timestamp: received_at,
+            },
+        );
+        self.evict_events(received_at).await;
+    }
+
+    /// Counts the event and, for price and score updates, refreshes the aggregates derived
+    /// from the value it carries.
+    async fn update_aggregates(
+        &mut self,
+        event_type: DashboardEventType,
+        event: Option<&SourceEvent>,
+        received_at: u64,
+    ) {
+        *self
+            .state
+            .event_counts
+            .get_mut_or_default(&event_type)
+            .await
+            .expect("Failed to read event counts") += 1;
+        self.count_event(event_type, received_at).await;
+        match event {
+            Some(SourceEvent::Price(PriceFeedEvent::PriceUpdate {
+                token,
+                price,
+                timestamp,
+                ..
+            })) => {
+                self.record_price(token.clone(), *price, *timestamp).await;
+            }
+            Some(SourceEvent::Score(IdentityScoreEvent::ScoreUpdate {
+                user_id,
+                new_score,
+                timestamp,
+                ..
+            })) => {
+                self.record_score(user_id.clone(), *new_score, *timestamp)
+                    .await;
+            }
+            None => {}
+        }
+    }
+
+    /// Adds an event received at `received_at` to the event rates of its type.
+    async fn count_event(&mut self, event_type: DashboardEventType, received_at: u64) {
+        for interval in CandleInterval::ALL {
+            let start = interval.bucket_start(received_at);
+            let buckets = self
+                .state
+                .event_rates
+                .load_entry_mut(&RateSeries {
+                    event_type,
+                    interval,
+                })
+                .await
+                .expect("Failed to load event rates");
+            let count = buckets
+                .get_mut_or_default(&BucketStart(start))
+                .await
+                .expect("Failed to read event rate");
+            *count += 1;
+            if *count == 1 {
+                prune_buckets(buckets, start, interval).await;
+            }
+        }
+    }
+
+    /// Updates the token's latest price and candles with a price committed at `timestamp`.
+    async fn record_price(&mut self, token: String, price: Price, timestamp: u64) {
+        for interval in CandleInterval::ALL {
+            let start = interval.bucket_start(timestamp);
+            let candles = self
+                .state
+                .candles
+                .load_entry_mut(&CandleSeries {
+                    token: token.clone(),
+                    interval,
+                })
+                .await
+                .expect("Failed to load candles");
+            match candles
+                .get_mut(&BucketStart(start))
+                .await
+                .expect("Failed to read candle")
+            {
+                Some(candle) => candle.record(price.to_f64(), timestamp),
+                None => {
+                    candles
+                        .insert(
+                            &BucketStart(start),
+                            Candle::new(start, price.to_f64(), timestamp),
+                        )
+                        .expect("Failed to store candle");
+                    prune_buckets(candles, start, interval).await;
+                }
+            }
+        }
+        let latest = self
+            .state
+            .latest_prices
+            .get(&token)
+            .await
+            .expect("Failed to read latest price");
+        if latest.is_some_and(|latest| latest.timestamp > timestamp) {
+            return;
+        }
+        let latest = LatestPrice {
+            token: token.clone(),
+            price,
+            timestamp,
+        };
+        self.state
+            .latest_prices
+            .insert(&token, latest)
+            .expect("Failed to store latest price");
+    }
+
+    /// Updates the user's latest score, the score ranking and the score histogram with a score
+    /// set at `timestamp`. Scores older than the latest one are ignored.
+    async fn record_score(&mut self, user_id: String, score: f64, timestamp: u64) {
+        let latest = self
+            .state
+            .latest_scores
+            .get(&user_id)
+            .await
+            .expect("Failed to read latest score");
+        if let Some(latest) = &latest {
+            if latest.timestamp > timestamp {
+                return;
+            }
+            self.state
+                .score_ranking
+                .remove(&RankedScore {
+                    score: latest.score,
+                    user_id: user_id.clone(),
+                })
+                .expect("Failed to update score ranking");
+            let users = self
+                .state
+                .score_histogram
+                .get_mut_or_default(&score_bucket(latest.score))
+                .await
+                .expect("Failed to read score histogram");
+            *users = users.saturating_sub(1);
+        }
+        *self
+            .state
+            .score_histogram
+            .get_mut_or_default(&score_bucket(score))
+            .await
+            .expect("Failed to read score histogram") += 1;
+        self.state
+            .score_ranking
+            .insert(&RankedScore {
+                score,
+                user_id: user_id.clone(),
+            })
+            .expect("Failed to update score ranking");
+        let latest = LatestScore {
+            user_id: user_id.clone(),
+            score,
+            timestamp,
+        };
+        self.state
+            .latest_scores
+            .insert(&user_id, latest)
+            .expect("Failed to store latest score");
+    }
+
+    /// Reads the event at `index` of `stream` through the application that emitted it, since
+    /// applications can only read their own streams. Returns `None` if the application did not
+    /// return an event.
+    fn read_source_event(
+        &mut self,
+        stream: &StreamSubscription,
+        index: u32,
+    ) -> Option<SourceEvent> {
+        match stream.event_type {
+            DashboardEventType::PriceUpdate => {
+                let operation = price_feed::Operation::ReadEvent {
+                    chain_id: stream.chain_id,
+                    index,
+                };
+                let response = self.runtime.call_application(
+                    false,
+                    stream.application_id.with_abi::<PriceFeedAbi>(),
+                    &operation,
+                );
+                let PriceFeedResponse::Event(event) = response else {
+                    return None;
+                };
+                Some(SourceEvent::Price(event))
+            }
+            DashboardEventType::ScoreUpdate => {
+                let operation = identity_score::Operation::ReadEvent {
+                    chain_id: stream.chain_id,
+                    index,
+                };
+                let response = self.runtime.call_application(
+                    false,
+                    stream.application_id.with_abi::<IdentityScoreAbi>(),
+                    &operation,
+                );
+                let Ok(IdentityScoreResponse::Event(event)) = response else {
+                    return None;
+                };
+                Some(SourceEvent::Score(event))
+            }
+            DashboardEventType::Other => None,
+        }
+    }
+
+    /// Drops the oldest events until the rest satisfy the retention policy.
+    async fn evict_events(&mut self, now: u64) {
+        let retention = self.runtime.application_parameters().retention;
+        while let Some(oldest) = self
+            .state
+            .received_events
+            .front()
+            .await
+            .expect("Failed to read events")
+        {
+            if !retention.evicts(&oldest, self.state.received_events.count(), now) {
+                break;
+            }
+            self.state.received_events.delete_front();
+            *self.state.dropped_events.get_mut() += 1;
+        }
+    }
+}
+
+/// Whether `payload` is a JSON document, as required of event payloads.
+fn is_json(payload: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(payload).is_ok()
+}
+
+/// Removes the buckets of a series that are more than `MAX_BUCKETS_PER_SERIES` intervals older
+/// than the one starting at `start`. Buckets are visited oldest first, so only the pruned ones
+/// are read.
+async fn prune_buckets<V>(
+    buckets: &mut CustomMapView<BucketStart, V>,
+    start: u64,
+    interval: CandleInterval,
+) where
+    V: Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned + 'static,
+{
+    let cutoff = start.saturating_sub(MAX_BUCKETS_PER_SERIES * interval.micros());
+    let mut old = Vec::new();
+    buckets
+        .for_each_index_while(|bucket| {
+            let is_old = bucket.0 < cutoff;
+            if is_old {
+                old.push(bucket);
+            }
+            Ok(is_old)
+        })
+        .await
+        .expect("Failed to read buckets");
+    for bucket in old {
+        buckets.remove(&bucket).expect("Failed to prune buckets");
+    }
+}
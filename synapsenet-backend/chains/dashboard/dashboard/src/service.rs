@@ -2,19 +2,30 @@
 
 mod state;
 
-use self::state::DashboardState;
+use self::state::{BucketStart, DashboardState};
 use async_graphql::{EmptySubscription, Object, Schema};
+use dashboard::{
+    AppInfo, Candle, CandleInterval, CandleSeries, DashboardAbi, DashboardEventType,
+    DashboardParameters, EventBucket, EventCount, EventRecord, HistogramBucket, LatestPrice,
+    LatestScore, Operation, RateSeries, SeriesPoint, StreamSubscription, MAX_BUCKETS_PER_SERIES,
+    SCORE_BUCKET_WIDTH,
+};
 use linera_sdk::{
-    base::WithServiceAbi,
+    linera_base_types::{ApplicationId, ChainId, WithServiceAbi},
     views::View,
     Service, ServiceRuntime,
 };
-use std::sync::Arc;
-use dashboard::DashboardAbi;
+use std::{collections::VecDeque, sync::Arc};
+
+/// Largest number of records returned by a single `events` or `receivedEvents` query.
+const MAX_EVENTS_PAGE: usize = 100;
+
+/// Largest number of users returned by a single `topScores` query.
+const MAX_TOP_SCORES: usize = 100;
 
 pub struct DashboardService {
-    state: DashboardState,
-    runtime: ServiceRuntime<Self>,
+    state: Arc<DashboardState>,
+    runtime: Arc<ServiceRuntime<Self>>,
 }
 
 linera_sdk::service!(DashboardService);
@@ -24,46 +35,400 @@ impl WithServiceAbi for DashboardService {
 }
 
 impl Service for DashboardService {
-    type Parameters = ();
+    type Parameters = DashboardParameters;
 
-    async fn load(runtime: ServiceRuntime<Self>) -> Self {
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = DashboardState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
-        DashboardService { state, runtime }
+        DashboardService {
+            state: Arc::new(state),
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    async fn handle_query(&self, request: Self::Query) -> Self::QueryResponse {
+        self.schema().execute(request).await
     }
+}
 
-    async fn graphql_query(&mut self, request: Self::Query) -> Self::QueryResponse {
-        let schema = Schema::build(
+type DashboardSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+impl DashboardService {
+    fn schema(&self) -> DashboardSchema {
+        Schema::build(
             QueryRoot {
-                received_events: self.state.received_events.get().clone(),
-                chain_id: *self.state.chain_id.get(),
+                state: self.state.clone(),
+                parameters: self.runtime.application_parameters(),
+                app_info: AppInfo {
+                    chain_id: self.runtime.chain_id(),
+                    application_id: self.runtime.application_id().forget_abi(),
+                    creator_chain_id: self.runtime.application_creator_chain_id(),
+                    created_at: *self.state.created_at.get(),
+                },
+            },
+            MutationRoot {
+                runtime: self.runtime.clone(),
             },
-            dashboard::Operation::mutation_root(),
             EmptySubscription,
         )
-        .finish();
-        schema.execute(request).await
-    }
-
-    async fn graphql_subscription(&mut self, _request: Self::Query) -> Self::QueryResponse {
-        // This is not supported in this example.
-        unimplemented!("GraphQL subscriptions are not supported by the Dashboard application")
+        .finish()
     }
 }
 
 pub struct QueryRoot {
-    received_events: Vec<String>,
-    chain_id: linera_base::data_types::ChainId,
+    state: Arc<DashboardState>,
+    parameters: DashboardParameters,
+    app_info: AppInfo,
 }
 
 #[Object]
 impl QueryRoot {
-    async fn received_events(&self) -> &Vec<String> {
-        &self.received_events
+    /// The newest `limit` (at most and by default `MAX_EVENTS_PAGE`) retained events, oldest
+    /// first.
+    async fn received_events(
+        &self,
+        limit: Option<usize>,
+    ) -> async_graphql::Result<Vec<EventRecord>> {
+        let limit = limit.unwrap_or(MAX_EVENTS_PAGE).min(MAX_EVENTS_PAGE);
+        Ok(self.state.received_events.read_back(limit).await?)
+    }
+
+    /// Retained events with a sequence number above `after`, oldest first, optionally only
+    /// those of `event_type`, and at most `limit` (`MAX_EVENTS_PAGE` by default) of them.
+    async fn events(
+        &self,
+        after: Option<u64>,
+        event_type: Option<DashboardEventType>,
+        limit: Option<usize>,
+    ) -> async_graphql::Result<Vec<EventRecord>> {
+        let events = &self.state.received_events;
+        let limit = limit.unwrap_or(MAX_EVENTS_PAGE).min(MAX_EVENTS_PAGE);
+        let next_sequence = *self.state.next_sequence.get();
+        // Sequence numbers are consecutive, so only the events after `after` are read.
+        let newer = after.map_or(events.count(), |after| {
+            usize::try_from(next_sequence.saturating_sub(after.saturating_add(1)))
+                .unwrap_or(usize::MAX)
+        });
+        let mut records = events.read_back(newer.min(events.count())).await?;
+        records.retain(|event| event_type.is_none_or(|event_type| event.event_type == event_type));
+        records.truncate(limit);
+        Ok(records)
+    }
+
+    /// Applications whose forwarded events are accepted.
+    async fn sources(&self) -> async_graphql::Result<Vec<ApplicationId>> {
+        Ok(self.state.sources.indices().await?)
+    }
+
+    /// Event streams read by the dashboard.
+    async fn subscriptions(&self) -> async_graphql::Result<Vec<StreamSubscription>> {
+        let mut subscriptions = Vec::new();
+        self.state
+            .subscriptions
+            .for_each_index(|subscription| {
+                subscriptions.push(subscription);
+                Ok(())
+            })
+            .await?;
+        Ok(subscriptions)
+    }
+
+    /// Number of recorded events per type, including evicted ones.
+    async fn event_counts(&self) -> async_graphql::Result<Vec<EventCount>> {
+        let mut counts = Vec::new();
+        self.state
+            .event_counts
+            .for_each_index_value(|event_type, count| {
+                counts.push(EventCount {
+                    event_type,
+                    count: *count,
+                });
+                Ok(())
+            })
+            .await?;
+        Ok(counts)
+    }
+
+    /// The most recent price of every token seen by the dashboard.
+    async fn latest_prices(&self) -> async_graphql::Result<Vec<LatestPrice>> {
+        let mut prices = Vec::new();
+        self.state
+            .latest_prices
+            .for_each_index_value(|_token, price| {
+                prices.push(price.into_owned());
+                Ok(())
+            })
+            .await?;
+        Ok(prices)
+    }
+
+    /// The most recent price of `token`.
+    async fn latest_price(&self, token: String) -> async_graphql::Result<Option<LatestPrice>> {
+        Ok(self.state.latest_prices.get(&token).await?)
+    }
+
+    /// The users with the highest latest scores, best first, limited to `limit` (default 10, at
+    /// most `MAX_TOP_SCORES`).
+    async fn top_scores(&self, limit: Option<usize>) -> async_graphql::Result<Vec<LatestScore>> {
+        let limit = limit.unwrap_or(10).min(MAX_TOP_SCORES);
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut user_ids = Vec::new();
+        self.state
+            .score_ranking
+            .for_each_index_while(|ranked| {
+                user_ids.push(ranked.user_id);
+                Ok(user_ids.len() < limit)
+            })
+            .await?;
+        let scores = self.state.latest_scores.multi_get(&user_ids).await?;
+        Ok(scores.into_iter().flatten().collect())
+    }
+
+    /// Candles of `token` at `interval`, oldest first. Only intervals starting within
+    /// `[from, to)`, in microseconds, are returned.
+    async fn candles(
+        &self,
+        token: String,
+        interval: CandleInterval,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> async_graphql::Result<Vec<Candle>> {
+        let mut candles = self.load_candles(token, interval).await?;
+        candles.retain(|candle| in_range(candle.start, from, to));
+        Ok(candles)
+    }
+
+    /// Average close of `token` over the last `window` intervals at `interval`, for each candle
+    /// starting within `[from, to)` that has a full window. Intervals without a candle count
+    /// with the close of the candle before them. The window spans at most
+    /// `MAX_BUCKETS_PER_SERIES` intervals, since older candles are pruned.
+    async fn moving_average(
+        &self,
+        token: String,
+        interval: CandleInterval,
+        window: usize,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> async_graphql::Result<Vec<SeriesPoint>> {
+        if window == 0 {
+            return Err("The window must contain at least one candle".into());
+        }
+        if window as u64 > MAX_BUCKETS_PER_SERIES {
+            return Err(format!(
+                "The window must not exceed the {MAX_BUCKETS_PER_SERIES} candles kept per series"
+            )
+            .into());
+        }
+        let candles = self.load_candles(token, interval).await?;
+        let mut closes = VecDeque::with_capacity(window + 1);
+        let mut points = Vec::new();
+        let mut previous: Option<&Candle> = None;
+        for candle in &candles {
+            if let Some(previous) = previous {
+                let missing = (candle.start - previous.start) / interval.micros() - 1;
+                for _ in 0..missing.min(window as u64) {
+                    push_close(&mut closes, previous.close, window);
+                }
+            }
+            push_close(&mut closes, candle.close, window);
+            if closes.len() == window && in_range(candle.start, from, to) {
+                points.push(SeriesPoint {
+                    start: candle.start,
+                    value: closes.iter().sum::<f64>() / window as f64,
+                });
+            }
+            previous = Some(candle);
+        }
+        Ok(points)
+    }
+
+    /// Number of `event_type` events received per `interval`, oldest first. Only intervals
+    /// starting within `[from, to)`, in microseconds, are returned; intervals without events
+    /// are omitted.
+    async fn event_rate(
+        &self,
+        event_type: DashboardEventType,
+        interval: CandleInterval,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> async_graphql::Result<Vec<EventBucket>> {
+        let series = RateSeries {
+            event_type,
+            interval,
+        };
+        let Some(rates) = self.state.event_rates.try_load_entry(&series).await? else {
+            return Ok(Vec::new());
+        };
+        let mut buckets = Vec::new();
+        rates
+            .for_each_index_value(|BucketStart(start), count| {
+                if in_range(start, from, to) {
+                    buckets.push(EventBucket {
+                        start,
+                        count: *count,
+                    });
+                }
+                Ok(())
+            })
+            .await?;
+        Ok(buckets)
+    }
+
+    /// Number of users per range of latest scores, lowest first. Empty ranges are omitted.
+    async fn score_histogram(&self) -> async_graphql::Result<Vec<HistogramBucket>> {
+        let mut buckets = Vec::new();
+        self.state
+            .score_histogram
+            .for_each_index_value(|bucket, users| {
+                if *users > 0 {
+                    buckets.push((bucket, *users));
+                }
+                Ok(())
+            })
+            .await?;
+        buckets.sort_by_key(|(bucket, _)| *bucket);
+        Ok(buckets
+            .into_iter()
+            .map(|(bucket, users)| HistogramBucket {
+                lower: bucket as f64 * SCORE_BUCKET_WIDTH,
+                upper: (bucket + 1) as f64 * SCORE_BUCKET_WIDTH,
+                users,
+            })
+            .collect())
     }
 
-    async fn chain_id(&self) -> &linera_base::data_types::ChainId {
-        &self.chain_id
+    /// Number of forwarded events ignored because their source was not accepted.
+    async fn rejected_messages(&self) -> u64 {
+        *self.state.rejected_messages.get()
+    }
+
+    /// Number of events ignored because their payload was not valid JSON.
+    async fn invalid_events(&self) -> u64 {
+        *self.state.invalid_events.get()
+    }
+
+    /// Number of events evicted by the retention policy.
+    async fn dropped_events(&self) -> u64 {
+        *self.state.dropped_events.get()
+    }
+
+    /// Settings the application was created with, including its retention policy.
+    async fn parameters(&self) -> &DashboardParameters {
+        &self.parameters
+    }
+
+    /// The chain and application ids of this instance, and when it was created.
+    async fn app_info(&self) -> &AppInfo {
+        &self.app_info
+    }
+}
+
+impl QueryRoot {
+    /// All retained candles of `token` at `interval`, oldest first.
+    async fn load_candles(
+        &self,
+        token: String,
+        interval: CandleInterval,
+    ) -> async_graphql::Result<Vec<Candle>> {
+        let series = CandleSeries { token, interval };
+        let Some(candles) = self.state.candles.try_load_entry(&series).await? else {
+            return Ok(Vec::new());
+        };
+        let mut result = Vec::new();
+        candles
+            .for_each_index_value(|_, candle| {
+                result.push(candle.into_owned());
+                Ok(())
+            })
+            .await?;
+        Ok(result)
+    }
+}
+
+/// Schedules the application's operations. `Forward` is left out: only applications can forward
+/// events, through `call_application`.
+pub struct MutationRoot {
+    runtime: Arc<ServiceRuntime<DashboardService>>,
+}
+
+#[Object]
+impl MutationRoot {
+    /// Records an event observed on another chain. `payload` must be a JSON document.
+    async fn receive_event(
+        &self,
+        event_type: DashboardEventType,
+        source_chain_id: ChainId,
+        source_application_id: Option<ApplicationId>,
+        payload: String,
+        emitted_at: u64,
+    ) -> [u8; 0] {
+        self.runtime.schedule_operation(&Operation::ReceiveEvent {
+            event_type,
+            source_chain_id,
+            source_application_id,
+            payload,
+            emitted_at,
+        });
+        []
+    }
+
+    /// Accepts events forwarded by `application_id`. Admin only.
+    async fn add_source(&self, application_id: ApplicationId) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::AddSource { application_id });
+        []
+    }
+
+    /// Stops accepting events forwarded by `application_id`. Admin only.
+    async fn remove_source(&self, application_id: ApplicationId) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::RemoveSource { application_id });
+        []
+    }
+
+    /// Reads the events of `event_type` that `application_id` emits on `chain_id`. Admin only.
+    async fn subscribe(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        event_type: DashboardEventType,
+    ) -> [u8; 0] {
+        self.runtime.schedule_operation(&Operation::Subscribe {
+            chain_id,
+            application_id,
+            event_type,
+        });
+        []
+    }
+
+    /// Stops reading the event stream of a previous `subscribe`. Admin only.
+    async fn unsubscribe(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        event_type: DashboardEventType,
+    ) -> [u8; 0] {
+        self.runtime.schedule_operation(&Operation::Unsubscribe {
+            chain_id,
+            application_id,
+            event_type,
+        });
+        []
+    }
+}
+
+/// Whether `start` lies within `[from, to)`; unset bounds do not apply.
+fn in_range(start: u64, from: Option<u64>, to: Option<u64>) -> bool {
+    from.is_none_or(|from| start >= from) && to.is_none_or(|to| start < to)
+}
+
+/// Appends `close` to the closes of a moving average, dropping the oldest one past `window`.
+fn push_close(closes: &mut VecDeque<f64>, close: f64, window: usize) {
+    closes.push_back(close);
+    if closes.len() > window {
+        closes.pop_front();
     }
 }
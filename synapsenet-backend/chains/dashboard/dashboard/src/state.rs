@@ -1,7 +1,113 @@
-use linera_sdk::views::{linera_views, RegisterView, RootView, ViewStorageContext};
+use dashboard::{
+    Candle, CandleSeries, DashboardEventType, EventRecord, LatestPrice, LatestScore, RateSeries,
+    StreamSubscription,
+};
+use linera_sdk::{
+    bcs,
+    linera_base_types::{AccountOwner, ApplicationId},
+    views::{
+        linera_views, CollectionView, CustomMapView, CustomSerialize, CustomSetView, MapView,
+        QueueView, RegisterView, RootView, SetView, ViewError, ViewStorageContext,
+    },
+};
 
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct DashboardState {
-    pub received_events: RegisterView<Vec<String>>,
+    /// Received events still within the retention policy, oldest first.
+    pub received_events: QueueView<EventRecord>,
+    /// Number of events evicted by the retention policy.
+    pub dropped_events: RegisterView<u64>,
+    /// Sequence number given to the next received event.
+    pub next_sequence: RegisterView<u64>,
+    /// Owner allowed to manage `sources`.
+    pub admin: RegisterView<Option<AccountOwner>>,
+    /// Applications whose forwarded events are accepted.
+    pub sources: SetView<ApplicationId>,
+    /// Number of forwarded events ignored because their source was not accepted.
+    pub rejected_messages: RegisterView<u64>,
+    /// Number of events ignored because their payload was not valid JSON.
+    pub invalid_events: RegisterView<u64>,
+    /// Event streams read by the dashboard.
+    pub subscriptions: SetView<StreamSubscription>,
+    /// Index of the next unrecorded event of each source stream, whether its events are
+    /// forwarded or read from the stream.
+    pub next_event_indices: MapView<StreamSubscription, u32>,
+    /// Number of recorded events per type, including evicted ones.
+    pub event_counts: MapView<DashboardEventType, u64>,
+    /// The most recent price of each token.
+    pub latest_prices: MapView<String, LatestPrice>,
+    /// The most recent score of each user.
+    pub latest_scores: MapView<String, LatestScore>,
+    /// The users' latest scores, best first, so that the top scores are read without visiting
+    /// every user.
+    pub score_ranking: CustomSetView<RankedScore>,
+    /// Price candles of each token and interval, by start of the interval.
+    pub candles: CollectionView<CandleSeries, CustomMapView<BucketStart, Candle>>,
+    /// Number of received events of each type and interval, by start of the interval.
+    pub event_rates: CollectionView<RateSeries, CustomMapView<BucketStart, u64>>,
+    /// Number of users per score bucket, as given by `dashboard::score_bucket`, based on their
+    /// latest scores.
+    pub score_histogram: MapView<i64, u64>,
+    /// When the application was instantiated, in microseconds.
+    pub created_at: RegisterView<u64>,
+}
+
+/// Start of a candle or event-rate bucket, in microseconds. Stored big-endian so that buckets
+/// are iterated oldest first.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct BucketStart(pub u64);
+
+impl CustomSerialize for BucketStart {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        let mut bytes = bcs::to_bytes(&self.0)?;
+        bytes.reverse();
+        Ok(bytes)
+    }
+
+    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        let mut bytes = bytes.to_vec();
+        bytes.reverse();
+        Ok(BucketStart(bcs::from_bytes(&bytes)?))
+    }
+}
+
+/// A user's latest score in `score_ranking`. Stored as the score's bits, mapped so that higher
+/// scores come first, followed by the user id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankedScore {
+    pub score: f64,
+    pub user_id: String,
+}
+
+impl CustomSerialize for RankedScore {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        // Flipping the sign bit of positive floats and every bit of negative ones orders their
+        // bits like the floats, and inverting the result puts higher scores first.
+        let bits = self.score.to_bits();
+        let ascending = if bits >> 63 == 0 {
+            bits | 1 << 63
+        } else {
+            !bits
+        };
+        let mut bytes = (!ascending).to_be_bytes().to_vec();
+        bytes.extend(bcs::to_bytes(&self.user_id)?);
+        Ok(bytes)
+    }
+
+    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        let (score, user_id) = bytes
+            .split_first_chunk::<8>()
+            .ok_or(ViewError::InconsistentEntries)?;
+        let ascending = !u64::from_be_bytes(*score);
+        let bits = if ascending >> 63 == 1 {
+            ascending & !(1 << 63)
+        } else {
+            !ascending
+        };
+        Ok(RankedScore {
+            score: f64::from_bits(bits),
+            user_id: bcs::from_bytes(user_id)?,
+        })
+    }
 }
@@ -0,0 +1,285 @@
+//! Integration tests of a dashboard aggregating the events of applications on other chains.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::BTreeSet;
+
+use dashboard::{
+    DashboardAbi, DashboardEventType, DashboardInstantiation, DashboardParameters, Operation,
+    RetentionPolicy,
+};
+use linera_sdk::{
+    linera_base_types::{AccountOwner, ApplicationId, ModuleId},
+    test::{ActiveChain, TestValidator},
+};
+use serde_json::json;
+use synapsenet_common::{
+    identity_score::{
+        self, IdentityScoreAbi, IdentityScoreInstantiation, IdentityScoreParameters, ScoreModel,
+    },
+    price_feed::{self, PriceFeedAbi, PriceFeedInstantiation, PriceFeedParameters},
+    Price,
+};
+
+/// A dashboard on its own chain, and a chain whose owner runs the source applications.
+struct Setup {
+    _validator: TestValidator,
+    dashboard_chain: ActiveChain,
+    dashboard_id: ApplicationId<DashboardAbi>,
+    source_chain: ActiveChain,
+    price_feed_module: ModuleId<PriceFeedAbi, PriceFeedParameters, PriceFeedInstantiation>,
+    identity_score_module:
+        ModuleId<IdentityScoreAbi, IdentityScoreParameters, IdentityScoreInstantiation>,
+}
+
+impl Setup {
+    async fn new() -> Self {
+        let (validator, module_id) = TestValidator::with_current_module::<
+            DashboardAbi,
+            DashboardParameters,
+            DashboardInstantiation,
+        >()
+        .await;
+        let mut dashboard_chain = validator.new_chain().await;
+        let dashboard_id = dashboard_chain
+            .create_application(
+                module_id,
+                DashboardParameters {
+                    chain_id: dashboard_chain.id(),
+                    retention: RetentionPolicy::default(),
+                },
+                DashboardInstantiation {
+                    admin: None,
+                    sources: Vec::new(),
+                },
+                vec![],
+            )
+            .await;
+        let source_chain = validator.new_chain().await;
+        let price_feed_module = source_chain
+            .publish_bytecode_files_in("../../price-feed/price-feed")
+            .await;
+        let identity_score_module = source_chain
+            .publish_bytecode_files_in("../../identity-score/identity-score")
+            .await;
+        Setup {
+            _validator: validator,
+            dashboard_chain,
+            dashboard_id,
+            source_chain,
+            price_feed_module,
+            identity_score_module,
+        }
+    }
+
+    /// Creates a price feed on the source chain whose publisher is the source chain's owner.
+    /// It forwards its prices to the dashboard if `forward` is set, and only emits them on its
+    /// stream otherwise.
+    async fn create_price_feed(&mut self, forward: bool) -> ApplicationId<PriceFeedAbi> {
+        let publisher = AccountOwner::from(self.source_chain.public_key());
+        let dashboard = forward.then(|| self.dashboard_id.forget_abi());
+        self.source_chain
+            .create_application(
+                self.price_feed_module,
+                PriceFeedParameters {
+                    quorum: 1,
+                    max_deviation_bps: 500,
+                    dashboard,
+                },
+                PriceFeedInstantiation {
+                    admin: None,
+                    publishers: BTreeSet::from([publisher]),
+                },
+                dashboard.into_iter().collect(),
+            )
+            .await
+    }
+
+    /// Creates an identity score on the source chain that only emits score changes on its
+    /// stream, administered by the source chain's owner.
+    async fn create_identity_score(&mut self) -> ApplicationId<IdentityScoreAbi> {
+        self.source_chain
+            .create_application(
+                self.identity_score_module,
+                IdentityScoreParameters {
+                    model: ScoreModel {
+                        baseline: 50.0,
+                        weights: Vec::new(),
+                        half_life_secs: None,
+                        min_score: 0.0,
+                        max_score: 100.0,
+                    },
+                    dashboard: None,
+                },
+                IdentityScoreInstantiation::default(),
+                vec![],
+            )
+            .await
+    }
+
+    /// Runs a dashboard operation signed by the dashboard's admin.
+    async fn execute(&self, operation: Operation) {
+        self.dashboard_chain
+            .add_block(|block| {
+                block.with_operation(self.dashboard_id, operation);
+            })
+            .await;
+    }
+
+    /// Publishes `value` as the price of ETH on `price_feed`.
+    async fn update_price(&self, price_feed: ApplicationId<PriceFeedAbi>, value: i128) {
+        self.source_chain
+            .add_block(|block| {
+                block.with_operation(
+                    price_feed,
+                    price_feed::Operation::UpdatePrice {
+                        token: "ETH".to_string(),
+                        price: Price::new(value, 2),
+                    },
+                );
+            })
+            .await;
+    }
+
+    /// Sets the score of `user_id` on `identity_score`.
+    async fn update_score(
+        &self,
+        identity_score: ApplicationId<IdentityScoreAbi>,
+        user_id: &str,
+        score: f64,
+    ) {
+        self.source_chain
+            .add_block(|block| {
+                block.with_operation(
+                    identity_score,
+                    identity_score::Operation::UpdateScore {
+                        user_id: user_id.to_string(),
+                        score,
+                        reason: "test".to_string(),
+                    },
+                );
+            })
+            .await;
+    }
+
+    async fn query(&self, query: &str) -> serde_json::Value {
+        self.dashboard_chain
+            .graphql_query(self.dashboard_id, query)
+            .await
+            .response
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn forwarded_events_are_recorded_once_and_only_from_sources() {
+    let mut setup = Setup::new().await;
+    let source = setup.create_price_feed(true).await;
+    let stranger = setup.create_price_feed(true).await;
+    setup
+        .execute(Operation::AddSource {
+            application_id: source.forget_abi(),
+        })
+        .await;
+
+    setup.update_price(source, 250_000).await;
+    setup.update_price(stranger, 100).await;
+    setup.dashboard_chain.handle_received_messages().await;
+
+    let response = setup
+        .query(
+            "query { \
+                latestPrice(token: \"ETH\") { price } \
+                events { sequence eventType } \
+                rejectedMessages \
+            }",
+        )
+        .await;
+    assert_eq!(
+        response["latestPrice"]["price"],
+        json!({ "value": "250000", "decimals": 2 })
+    );
+    assert_eq!(
+        response["events"],
+        json!([{ "sequence": 0, "eventType": "PRICE_UPDATE" }])
+    );
+    assert_eq!(response["rejectedMessages"], json!(1));
+
+    // The forwarded price is also on the source's stream, and is not recorded again when the
+    // stream is read.
+    setup
+        .execute(Operation::Subscribe {
+            chain_id: setup.source_chain.id(),
+            application_id: source.forget_abi(),
+            event_type: DashboardEventType::PriceUpdate,
+        })
+        .await;
+    setup.dashboard_chain.handle_new_events().await;
+
+    let response = setup
+        .query("query { events { sequence } eventCounts { count } invalidEvents }")
+        .await;
+    assert_eq!(response["events"], json!([{ "sequence": 0 }]));
+    assert_eq!(response["eventCounts"], json!([{ "count": 1 }]));
+    assert_eq!(response["invalidEvents"], json!(0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscribed_streams_are_aggregated() {
+    let mut setup = Setup::new().await;
+    let price_feed = setup.create_price_feed(false).await;
+    let identity_score = setup.create_identity_score().await;
+    for (application_id, event_type) in [
+        (price_feed.forget_abi(), DashboardEventType::PriceUpdate),
+        (identity_score.forget_abi(), DashboardEventType::ScoreUpdate),
+    ] {
+        setup.execute(Operation::AddSource { application_id }).await;
+        setup
+            .execute(Operation::Subscribe {
+                chain_id: setup.source_chain.id(),
+                application_id,
+                event_type,
+            })
+            .await;
+    }
+
+    setup.update_price(price_feed, 250_000).await;
+    setup.update_score(identity_score, "alice", 42.0).await;
+    setup.update_score(identity_score, "bob", 87.0).await;
+    setup.update_score(identity_score, "alice", 91.0).await;
+    setup.dashboard_chain.handle_new_events().await;
+
+    let response = setup
+        .query(
+            "query { \
+                latestPrices { token price } \
+                topScores { userId score } \
+                scoreHistogram { lower users } \
+                eventCounts { eventType count } \
+                invalidEvents \
+            }",
+        )
+        .await;
+    assert_eq!(
+        response["latestPrices"],
+        json!([{ "token": "ETH", "price": { "value": "250000", "decimals": 2 } }])
+    );
+    assert_eq!(
+        response["topScores"],
+        json!([
+            { "userId": "alice", "score": 91.0 },
+            { "userId": "bob", "score": 87.0 },
+        ])
+    );
+    assert_eq!(
+        response["scoreHistogram"],
+        json!([{ "lower": 80.0, "users": 1 }, { "lower": 90.0, "users": 1 }])
+    );
+    assert_eq!(
+        response["eventCounts"],
+        json!([
+            { "eventType": "PRICE_UPDATE", "count": 1 },
+            { "eventType": "SCORE_UPDATE", "count": 3 },
+        ])
+    );
+    assert_eq!(response["invalidEvents"], json!(0));
+}
@@ -0,0 +1,269 @@
+//! Integration tests for the dashboard application, run against a test validator.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use dashboard::{
+    DashboardAbi, DashboardEvent, DashboardEventType, DashboardInstantiation, DashboardParameters,
+    Operation, RetentionPolicy, SourceEvent, DASHBOARD_STREAM_NAME, MAX_BUCKETS_PER_SERIES,
+};
+use linera_sdk::{
+    bcs,
+    linera_base_types::{ApplicationId, StreamName},
+    test::{ActiveChain, TestValidator},
+};
+use serde_json::json;
+use synapsenet_common::{price_feed::PriceFeedEvent, Price};
+
+/// Creates a dashboard aggregating events on its creator chain, whose admin is that chain's
+/// owner.
+async fn create_dashboard() -> (TestValidator, ApplicationId<DashboardAbi>, ActiveChain) {
+    let (validator, module_id) = TestValidator::with_current_module::<
+        DashboardAbi,
+        DashboardParameters,
+        DashboardInstantiation,
+    >()
+    .await;
+    let mut chain = validator.new_chain().await;
+    let application_id = chain
+        .create_application(
+            module_id,
+            DashboardParameters {
+                chain_id: chain.id(),
+                retention: RetentionPolicy::default(),
+            },
+            DashboardInstantiation {
+                admin: None,
+                sources: Vec::new(),
+            },
+            vec![],
+        )
+        .await;
+    (validator, application_id, chain)
+}
+
+async fn receive_event(
+    chain: &ActiveChain,
+    application_id: ApplicationId<DashboardAbi>,
+    event_type: DashboardEventType,
+    payload: String,
+) -> Vec<DashboardEvent> {
+    let operation = Operation::ReceiveEvent {
+        event_type,
+        source_chain_id: chain.id(),
+        source_application_id: None,
+        payload,
+        emitted_at: 0,
+    };
+    let (certificate, _) = chain
+        .add_block(|block| {
+            block.with_operation(application_id, operation);
+        })
+        .await;
+    // The events the dashboard emitted while recording it.
+    certificate.block().body.events[0]
+        .iter()
+        .filter(|event| event.stream_id.stream_name == StreamName::from(DASHBOARD_STREAM_NAME))
+        .map(|event| bcs::from_bytes(&event.value).expect("Invalid dashboard event"))
+        .collect()
+}
+
+/// Checks that the service turns `operation`'s GraphQL mutation into exactly `operation`.
+async fn assert_mutation_schedules(
+    chain: &ActiveChain,
+    application_id: ApplicationId<DashboardAbi>,
+    operation: Operation,
+) {
+    let mutation = operation
+        .to_mutation()
+        .expect("The operation has no mutation");
+    let outcome = chain.graphql_query(application_id, mutation.as_str()).await;
+    let bytes = bcs::to_bytes(&operation).expect("Failed to serialize operation");
+    assert_eq!(
+        serde_json::to_value(&outcome.operations).expect("Failed to serialize operations"),
+        json!([{ "User": { "application_id": application_id.forget_abi(), "bytes": bytes } }]),
+        "{mutation}"
+    );
+}
+
+fn price_update(value: i128, timestamp: u64) -> String {
+    SourceEvent::Price(PriceFeedEvent::PriceUpdate {
+        token: "ETH".to_string(),
+        price: Price::new(value, 2),
+        round: None,
+        timestamp,
+    })
+    .to_json()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_are_paged_by_sequence() {
+    let (_validator, application_id, chain) = create_dashboard().await;
+    for note in 0..3 {
+        receive_event(
+            &chain,
+            application_id,
+            DashboardEventType::Other,
+            json!({ "note": note }).to_string(),
+        )
+        .await;
+    }
+    let emitted = receive_event(
+        &chain,
+        application_id,
+        DashboardEventType::PriceUpdate,
+        price_update(250_000, 0),
+    )
+    .await;
+    assert!(matches!(
+        emitted[..],
+        [DashboardEvent::EventReceived {
+            sequence: 3,
+            event_type: DashboardEventType::PriceUpdate,
+            ..
+        }]
+    ));
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                all: events { sequence } \
+                page: events(after: 0, limit: 2) { sequence } \
+                prices: events(eventType: PRICE_UPDATE) { sequence } \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(
+        response["all"],
+        json!([
+            { "sequence": 0 },
+            { "sequence": 1 },
+            { "sequence": 2 },
+            { "sequence": 3 },
+        ])
+    );
+    assert_eq!(
+        response["page"],
+        json!([{ "sequence": 1 }, { "sequence": 2 }])
+    );
+    assert_eq!(response["prices"], json!([{ "sequence": 3 }]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_payloads_are_only_counted() {
+    let (_validator, application_id, chain) = create_dashboard().await;
+    let emitted = receive_event(
+        &chain,
+        application_id,
+        DashboardEventType::Other,
+        "not json".to_string(),
+    )
+    .await;
+    assert!(emitted.is_empty());
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { invalidEvents events { sequence } }",
+        )
+        .await
+        .response;
+    assert_eq!(response["invalidEvents"], json!(1));
+    assert_eq!(response["events"], json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prices_build_candles_and_moving_averages() {
+    let (_validator, application_id, chain) = create_dashboard().await;
+    let minute = 60_000_000;
+    // Two prices in the first minute, none in the second and one in the third.
+    for (value, timestamp) in [
+        (10_000, minute),
+        (10_100, minute + 10),
+        (10_400, 3 * minute),
+    ] {
+        receive_event(
+            &chain,
+            application_id,
+            DashboardEventType::PriceUpdate,
+            price_update(value, timestamp),
+        )
+        .await;
+    }
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                latestPrice(token: \"ETH\") { timestamp } \
+                candles(token: \"ETH\", interval: MINUTE) { start open close updates } \
+                movingAverage(token: \"ETH\", interval: MINUTE, window: 2) { start value } \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(response["latestPrice"]["timestamp"], json!(3 * minute));
+    assert_eq!(
+        response["candles"],
+        json!([
+            { "start": minute, "open": 100.0, "close": 101.0, "updates": 2 },
+            { "start": 3 * minute, "open": 104.0, "close": 104.0, "updates": 1 },
+        ])
+    );
+    // The second minute counts with the close of the first.
+    assert_eq!(
+        response["movingAverage"],
+        json!([{ "start": 3 * minute, "value": 102.5 }])
+    );
+    // A window longer than the kept candles is refused rather than allocated.
+    let query = format!(
+        "query {{ movingAverage(token: \"ETH\", interval: MINUTE, window: {}) {{ value }} }}",
+        MAX_BUCKETS_PER_SERIES + 1
+    );
+    assert!(chain
+        .try_graphql_query(application_id, query)
+        .await
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mutations_schedule_their_operations() {
+    let (_validator, application_id, chain) = create_dashboard().await;
+    let source = application_id.forget_abi();
+    let operations = [
+        Operation::ReceiveEvent {
+            event_type: DashboardEventType::ScoreUpdate,
+            source_chain_id: chain.id(),
+            source_application_id: Some(source),
+            payload: "{\"user_id\": \"alice\"}\n".to_string(),
+            emitted_at: 1_700_000_000_000_000,
+        },
+        Operation::ReceiveEvent {
+            event_type: DashboardEventType::Other,
+            source_chain_id: chain.id(),
+            source_application_id: None,
+            payload: String::new(),
+            emitted_at: 0,
+        },
+        Operation::AddSource {
+            application_id: source,
+        },
+        Operation::RemoveSource {
+            application_id: source,
+        },
+        Operation::Subscribe {
+            chain_id: chain.id(),
+            application_id: source,
+            event_type: DashboardEventType::PriceUpdate,
+        },
+        Operation::Unsubscribe {
+            chain_id: chain.id(),
+            application_id: source,
+            event_type: DashboardEventType::PriceUpdate,
+        },
+    ];
+    for operation in operations {
+        assert_mutation_schedules(&chain, application_id, operation).await;
+    }
+}
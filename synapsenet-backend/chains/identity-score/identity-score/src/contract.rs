@@ -3,12 +3,17 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::WithContractAbi,
+    linera_base_types::{AccountOwner, StreamName, WithContractAbi},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
 
-use identity_score::{Operation, IdentityScoreEvent};
+use identity_score::{
+    check_finite, check_user_id, Attestation, IdentityScoreError, IdentityScoreEvent,
+    IdentityScoreInstantiation, IdentityScoreParameters, IdentityScoreResponse, Operation,
+    ScoreChange, ScoreRecord, ScoreStatus, UserSignals, SCORE_STREAM_NAME,
+};
+use synapsenet_common::dashboard::{self, DashboardAbi, SourceEvent};
 
 use self::state::IdentityScoreState;
 
@@ -25,8 +30,8 @@ impl WithContractAbi for IdentityScoreContract {
 
 impl Contract for IdentityScoreContract {
     type Message = ();
-    type Parameters = ();
-    type InstantiationArgument = ();
+    type Parameters = IdentityScoreParameters;
+    type InstantiationArgument = IdentityScoreInstantiation;
     type EventValue = IdentityScoreEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
@@ -36,20 +41,185 @@ impl Contract for IdentityScoreContract {
         IdentityScoreContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
-        self.runtime.application_parameters();
-        // Chain ID available via self.runtime.chain_id() when needed
+    async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
+        let model = self.runtime.application_parameters().model;
+        assert!(
+            model.check().is_ok(),
+            "The score model must only contain finite numbers"
+        );
+        assert!(
+            model.check_score(model.baseline).is_ok(),
+            "The baseline must lie within the score range"
+        );
+        let admin = argument
+            .admin
+            .or_else(|| self.runtime.authenticated_signer());
+        self.state.admin.set(admin);
+        for issuer in &argument.signal_issuers {
+            self.state
+                .signal_issuers
+                .insert(issuer)
+                .expect("Failed to add signal issuer");
+        }
+        self.state
+            .created_at
+            .set(self.runtime.system_time().micros());
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         match operation {
             Operation::UpdateScore { user_id, score, reason } => {
-                let timestamp = self.runtime.system_time().micros();
-                self.state.scores.get_mut().insert(user_id.clone(), score);
-                self.state.last_update.set(timestamp);
-                // Event emission handled by framework via EventValue type
+                self.check_admin()?;
+                let model = self.runtime.application_parameters().model;
+                check_user_id(&user_id)?;
+                model.check_score(score)?;
+                let mut signals = self.decayed_signals(&user_id).await;
+                model.override_score(&mut signals, score);
+                self.state
+                    .signals
+                    .insert(&user_id, signals)
+                    .expect("Failed to store signals");
+                self.set_score(user_id, score, reason).await;
+            }
+            Operation::Register { user_id } => {
+                check_user_id(&user_id)?;
+                let owner = self.signer()?;
+                if let Some(user_id) = self
+                    .state
+                    .user_ids
+                    .get(&owner)
+                    .await
+                    .expect("Failed to read user ids")
+                {
+                    return Err(IdentityScoreError::OwnerAlreadyRegistered { user_id });
+                }
+                if self.owner_of(&user_id).await.is_some() {
+                    return Err(IdentityScoreError::UserIdTaken { user_id });
+                }
+                self.state
+                    .owners
+                    .insert(&user_id, owner)
+                    .expect("Failed to store owner");
+                self.state
+                    .user_ids
+                    .insert(&owner, user_id)
+                    .expect("Failed to store user id");
+            }
+            Operation::RecordSignal {
+                user_id,
+                kind,
+                value,
+                reason,
+            } => {
+                let model = self.runtime.application_parameters().model;
+                check_user_id(&user_id)?;
+                check_finite("value", value)?;
+                let signer = self.signer()?;
+                if kind.is_self_reported() {
+                    if self.owner_of(&user_id).await != Some(signer) {
+                        return Err(IdentityScoreError::NotOwner { user_id });
+                    }
+                } else if !self.is_signal_issuer(&signer).await {
+                    return Err(IdentityScoreError::Unauthorized { owner: signer });
+                }
+                let mut signals = self.decayed_signals(&user_id).await;
+                model.accumulate(&mut signals, kind, value);
+                model.check_signals(&signals)?;
+                let score = model.settle_score(&mut signals);
+                self.state
+                    .signals
+                    .insert(&user_id, signals)
+                    .expect("Failed to store signals");
+                self.set_score(user_id, score, reason).await;
+            }
+            Operation::Attest {
+                subject,
+                claim,
+                expiry,
+            } => {
+                let issuer = self.signer()?;
+                let now = self.runtime.system_time().micros();
+                if let Some(expiry) = expiry.filter(|expiry| *expiry <= now) {
+                    return Err(IdentityScoreError::ExpiryInPast { expiry });
+                }
+                let mut attestations = self.read_attestations(&subject).await;
+                attestations.retain(|attestation| {
+                    attestation.issuer != issuer || attestation.claim != claim
+                });
+                attestations.push(Attestation {
+                    issuer,
+                    claim,
+                    issued_at: now,
+                    expiry,
+                    revoked_at: None,
+                });
+                self.state
+                    .attestations
+                    .insert(&subject, attestations)
+                    .expect("Failed to store attestations");
+            }
+            Operation::Revoke { subject, claim } => {
+                let issuer = self.signer()?;
+                let now = self.runtime.system_time().micros();
+                let mut attestations = self.read_attestations(&subject).await;
+                let attestation = attestations
+                    .iter_mut()
+                    .find(|attestation| {
+                        attestation.issuer == issuer
+                            && attestation.claim == claim
+                            && attestation.revoked_at.is_none()
+                    })
+                    .ok_or_else(|| IdentityScoreError::AttestationNotFound {
+                        subject: subject.clone(),
+                        claim,
+                    })?;
+                attestation.revoked_at = Some(now);
+                self.state
+                    .attestations
+                    .insert(&subject, attestations)
+                    .expect("Failed to store attestations");
+            }
+            Operation::AddSignalIssuer { owner } => {
+                self.check_admin()?;
+                self.state
+                    .signal_issuers
+                    .insert(&owner)
+                    .expect("Failed to add signal issuer");
+            }
+            Operation::RemoveSignalIssuer { owner } => {
+                self.check_admin()?;
+                self.state
+                    .signal_issuers
+                    .remove(&owner)
+                    .expect("Failed to remove signal issuer");
+            }
+            Operation::ReadEvent { chain_id, index } => {
+                let event =
+                    self.runtime
+                        .read_event(chain_id, StreamName::from(SCORE_STREAM_NAME), index);
+                return Ok(IdentityScoreResponse::Event(event));
+            }
+            Operation::GetScore { user_id } => {
+                let model = self.runtime.application_parameters().model;
+                let now = self.runtime.system_time().micros();
+                let status = ScoreStatus {
+                    score: self
+                        .read_score(&user_id)
+                        .await
+                        .map(|record| model.decayed(&record, now)),
+                    owner: self.owner_of(&user_id).await,
+                    attested_claims: self
+                        .read_attestations(&user_id)
+                        .await
+                        .into_iter()
+                        .filter(|attestation| attestation.is_valid(now))
+                        .map(|attestation| attestation.claim)
+                        .collect(),
+                };
+                return Ok(IdentityScoreResponse::Status(status));
             }
         }
+        Ok(IdentityScoreResponse::Ok)
     }
 
     async fn execute_message(&mut self, _message: Self::Message) {}
@@ -58,3 +228,130 @@ impl Contract for IdentityScoreContract {
         self.state.save().await.expect("Failed to save state");
     }
 }
+
+impl IdentityScoreContract {
+    fn signer(&mut self) -> Result<AccountOwner, IdentityScoreError> {
+        self.runtime
+            .authenticated_signer()
+            .ok_or(IdentityScoreError::Unauthenticated)
+    }
+
+    /// Returns an error unless the operation was signed by the admin.
+    fn check_admin(&mut self) -> Result<(), IdentityScoreError> {
+        let signer = self.signer()?;
+        if self.state.admin.get().as_ref() != Some(&signer) {
+            return Err(IdentityScoreError::Unauthorized { owner: signer });
+        }
+        Ok(())
+    }
+
+    async fn is_signal_issuer(&self, owner: &AccountOwner) -> bool {
+        self.state
+            .signal_issuers
+            .contains(owner)
+            .await
+            .expect("Failed to read signal issuers")
+    }
+
+    async fn owner_of(&self, user_id: &str) -> Option<AccountOwner> {
+        self.state
+            .owners
+            .get(user_id)
+            .await
+            .expect("Failed to read owners")
+    }
+
+    async fn read_attestations(&self, subject: &str) -> Vec<Attestation> {
+        self.state
+            .attestations
+            .get(subject)
+            .await
+            .expect("Failed to read attestations")
+            .unwrap_or_default()
+    }
+
+    async fn read_score(&self, user_id: &str) -> Option<ScoreRecord> {
+        self.state
+            .scores
+            .get(user_id)
+            .await
+            .expect("Failed to read score")
+    }
+
+    /// The signals of `user_id`, decayed up to now.
+    async fn decayed_signals(&mut self, user_id: &str) -> UserSignals {
+        let mut signals = self
+            .state
+            .signals
+            .get(user_id)
+            .await
+            .expect("Failed to read signals")
+            .unwrap_or_default();
+        if let Some(record) = self.read_score(user_id).await {
+            let now = self.runtime.system_time().micros();
+            let model = self.runtime.application_parameters().model;
+            model.decay_signals(&mut signals, now.saturating_sub(record.last_activity));
+        }
+        signals
+    }
+
+    /// Sets the score of `user_id`, appends the change to their history and emits it on the
+    /// score stream.
+    async fn set_score(&mut self, user_id: String, score: f64, reason: String) {
+        let timestamp = self.runtime.system_time().micros();
+        let model = self.runtime.application_parameters().model;
+        let old_score = self
+            .read_score(&user_id)
+            .await
+            .map(|record| model.decayed(&record, timestamp));
+        let record = ScoreRecord {
+            score,
+            last_activity: timestamp,
+        };
+        self.state
+            .scores
+            .insert(&user_id, record)
+            .expect("Failed to store score");
+        self.state.last_update.set(timestamp);
+        let signer = self.runtime.authenticated_signer();
+        let history = self
+            .state
+            .history
+            .load_entry_mut(&user_id)
+            .await
+            .expect("Failed to load score history");
+        history.push(ScoreChange {
+            sequence: history.count() as u64,
+            score,
+            delta: score - old_score.unwrap_or_default(),
+            reason: reason.clone(),
+            timestamp,
+            signer,
+        });
+        let event = IdentityScoreEvent::ScoreUpdate {
+            user_id,
+            old_score,
+            new_score: score,
+            reason,
+            timestamp,
+        };
+        let index = self
+            .runtime
+            .emit(StreamName::from(SCORE_STREAM_NAME), &event);
+        self.forward_to_dashboard(event, index);
+    }
+
+    /// Forwards `event`, emitted at `index` of the score stream, to the configured dashboard
+    /// application, which relays it to the dashboard chain.
+    fn forward_to_dashboard(&mut self, event: IdentityScoreEvent, index: u32) {
+        let Some(dashboard) = self.runtime.application_parameters().dashboard else {
+            return;
+        };
+        let operation = dashboard::Operation::Forward {
+            event: SourceEvent::Score(event),
+            index,
+        };
+        self.runtime
+            .call_application(true, dashboard.with_abi::<DashboardAbi>(), &operation);
+    }
+}
@@ -1,28 +1,4 @@
-use async_graphql::{Request, Response};
-use linera_sdk::{
-    graphql::GraphQLMutationRoot,
-    linera_base_types::{ContractAbi, ServiceAbi},
-};
-use serde::{Deserialize, Serialize};
+//! The identity-score application. Its ABI and types are defined in `synapsenet-common`, so that the
+//! other applications and the SDK share them.
 
-pub struct IdentityScoreAbi;
-
-impl ContractAbi for IdentityScoreAbi {
-    type Operation = Operation;
-    type Response = ();
-}
-
-impl ServiceAbi for IdentityScoreAbi {
-    type Query = Request;
-    type QueryResponse = Response;
-}
-
-#[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
-pub enum Operation {
-    UpdateScore { user_id: String, score: f64, reason: String },
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub enum IdentityScoreEvent {
-    ScoreUpdate { user_id: String, score: f64, reason: String, timestamp: u64 },
-}
+pub use synapsenet_common::{identity_score::*, AppInfo, Score, UserId};
@@ -3,18 +3,32 @@
 mod state;
 
 use self::state::IdentityScoreState;
-use async_graphql::{EmptySubscription, Object, Schema};
+use async_graphql::{
+    connection::{Connection, Edge},
+    EmptySubscription, Object, Schema,
+};
+use identity_score::{
+    AppInfo, Attestation, IdentityScoreAbi, IdentityScoreParameters, Operation, ScoreChange,
+    ScoreModel, ScoreRecord, SignalKind, UserScore, UserSignals,
+};
 use linera_sdk::{
-    base::WithServiceAbi,
+    bcs,
+    linera_base_types::{AccountOwner, WithServiceAbi},
     views::View,
     Service, ServiceRuntime,
 };
 use std::sync::Arc;
-use identity_score::IdentityScoreAbi;
+
+/// Largest number of history entries returned by a single `scoreHistory` or `scoreChanges`
+/// query.
+const MAX_HISTORY_PAGE: usize = 100;
+
+/// Largest number of users returned by a single `scores` query.
+const MAX_SCORES_PAGE: usize = 100;
 
 pub struct IdentityScoreService {
-    state: IdentityScoreState,
-    runtime: ServiceRuntime<Self>,
+    state: Arc<IdentityScoreState>,
+    runtime: Arc<ServiceRuntime<Self>>,
 }
 
 linera_sdk::service!(IdentityScoreService);
@@ -24,52 +38,296 @@ impl WithServiceAbi for IdentityScoreService {
 }
 
 impl Service for IdentityScoreService {
-    type Parameters = ();
+    type Parameters = IdentityScoreParameters;
 
-    async fn load(runtime: ServiceRuntime<Self>) -> Self {
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = IdentityScoreState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
-        IdentityScoreService { state, runtime }
+        IdentityScoreService {
+            state: Arc::new(state),
+            runtime: Arc::new(runtime),
+        }
     }
 
-    async fn graphql_query(&mut self, request: Self::Query) -> Self::QueryResponse {
-        let schema = Schema::build(
+    async fn handle_query(&self, request: Self::Query) -> Self::QueryResponse {
+        self.schema().execute(request).await
+    }
+}
+
+type IdentityScoreSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+impl IdentityScoreService {
+    fn schema(&self) -> IdentityScoreSchema {
+        Schema::build(
             QueryRoot {
-                scores: self.state.scores.get().clone(),
+                state: self.state.clone(),
+                model: self.runtime.application_parameters().model,
+                now: self.runtime.system_time().micros(),
                 last_update: *self.state.last_update.get(),
-                chain_id: *self.state.chain_id.get(),
+                app_info: AppInfo {
+                    chain_id: self.runtime.chain_id(),
+                    application_id: self.runtime.application_id().forget_abi(),
+                    creator_chain_id: self.runtime.application_creator_chain_id(),
+                    created_at: *self.state.created_at.get(),
+                },
+            },
+            MutationRoot {
+                runtime: self.runtime.clone(),
             },
-            identity_score::Operation::mutation_root(),
             EmptySubscription,
         )
-        .finish();
-        schema.execute(request).await
-    }
-
-    async fn graphql_subscription(&mut self, _request: Self::Query) -> Self::QueryResponse {
-        // This is not supported in this example.
-        unimplemented!("GraphQL subscriptions are not supported by the IdentityScore application")
+        .finish()
     }
 }
 
 pub struct QueryRoot {
-    scores: std::collections::HashMap<String, f64>,
+    state: Arc<IdentityScoreState>,
+    model: ScoreModel,
+    /// Time of the query in microseconds, up to which scores are decayed.
+    now: u64,
     last_update: u64,
-    chain_id: linera_base::data_types::ChainId,
+    app_info: AppInfo,
 }
 
 #[Object]
 impl QueryRoot {
-    async fn scores(&self) -> &std::collections::HashMap<String, f64> {
-        &self.scores
+    /// Current scores in storage order. Pass the last cursor of a page as `after` to fetch the
+    /// next one.
+    async fn scores(
+        &self,
+        first: Option<usize>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Connection<String, UserScore>> {
+        let limit = first.unwrap_or(MAX_SCORES_PAGE).min(MAX_SCORES_PAGE);
+        // Indices are visited in the order of their serialized keys, so the cursor is compared
+        // in that form.
+        let after_key = after.as_ref().map(bcs::to_bytes).transpose()?;
+        let mut user_ids = Vec::new();
+        self.state
+            .scores
+            .for_each_index_while(|user_id| {
+                if let Some(after_key) = &after_key {
+                    if bcs::to_bytes(&user_id)? <= *after_key {
+                        return Ok(true);
+                    }
+                }
+                user_ids.push(user_id);
+                Ok(user_ids.len() <= limit)
+            })
+            .await?;
+        let has_next = user_ids.len() > limit;
+        user_ids.truncate(limit);
+        // Only the records of the page are read.
+        let records = self.state.scores.multi_get(&user_ids).await?;
+
+        let mut connection = Connection::new(after.is_some(), has_next);
+        connection.edges = user_ids
+            .into_iter()
+            .zip(records)
+            .filter_map(|(user_id, record)| {
+                let node = self.to_user_score(user_id.clone(), &record?);
+                Some(Edge::new(user_id, node))
+            })
+            .collect();
+        Ok(connection)
+    }
+
+    /// The current score of `user_id`.
+    async fn score(&self, user_id: String) -> async_graphql::Result<Option<UserScore>> {
+        self.user_score(&user_id).await
+    }
+
+    /// The formula used to derive scores from signals.
+    async fn score_model(&self) -> &ScoreModel {
+        &self.model
+    }
+
+    /// Owner allowed to set scores and manage signal issuers.
+    async fn admin(&self) -> Option<AccountOwner> {
+        *self.state.admin.get()
+    }
+
+    /// Owners currently allowed to record signals that are not self-reported.
+    async fn signal_issuers(&self) -> async_graphql::Result<Vec<AccountOwner>> {
+        Ok(self.state.signal_issuers.indices().await?)
+    }
+
+    /// The accumulated signals of `user_id`, as of their last score change.
+    async fn signals(&self, user_id: String) -> async_graphql::Result<UserSignals> {
+        Ok(self.state.signals.get(&user_id).await?.unwrap_or_default())
+    }
+
+    /// The owner that claimed `user_id`.
+    async fn owner(&self, user_id: String) -> async_graphql::Result<Option<AccountOwner>> {
+        Ok(self.state.owners.get(&user_id).await?)
+    }
+
+    /// The user id claimed by `owner`.
+    async fn user_id(&self, owner: AccountOwner) -> async_graphql::Result<Option<String>> {
+        Ok(self.state.user_ids.get(&owner).await?)
+    }
+
+    /// Attestations about `subject` that are currently valid, or all of them including revoked
+    /// and expired ones if `include_invalid` is set.
+    async fn attestations(
+        &self,
+        subject: String,
+        include_invalid: Option<bool>,
+    ) -> async_graphql::Result<Vec<Attestation>> {
+        let mut attestations = self
+            .state
+            .attestations
+            .get(&subject)
+            .await?
+            .unwrap_or_default();
+        if !include_invalid.unwrap_or(false) {
+            attestations.retain(|attestation| attestation.is_valid(self.now));
+        }
+        Ok(attestations)
+    }
+
+    /// Score changes of `user_id`, newest first. `before` is an exclusive `sequence` cursor
+    /// for fetching older pages.
+    async fn score_history(
+        &self,
+        user_id: String,
+        limit: Option<usize>,
+        before: Option<u64>,
+    ) -> async_graphql::Result<Vec<ScoreChange>> {
+        let Some(history) = self.state.history.try_load_entry(&user_id).await? else {
+            return Ok(Vec::new());
+        };
+        let limit = limit.unwrap_or(MAX_HISTORY_PAGE).min(MAX_HISTORY_PAGE);
+        let end = before
+            .map_or(history.count(), |before| {
+                usize::try_from(before).unwrap_or(usize::MAX)
+            })
+            .min(history.count());
+        let mut changes = history.read(end.saturating_sub(limit)..end).await?;
+        changes.reverse();
+        Ok(changes)
+    }
+
+    /// Score changes of `user_id` with a sequence above `after`, oldest first and at most
+    /// `MAX_HISTORY_PAGE` of them.
+    async fn score_changes(
+        &self,
+        user_id: String,
+        after: Option<u64>,
+    ) -> async_graphql::Result<Vec<ScoreChange>> {
+        let Some(history) = self.state.history.try_load_entry(&user_id).await? else {
+            return Ok(Vec::new());
+        };
+        let start = after
+            .map_or(0, |after| {
+                usize::try_from(after)
+                    .unwrap_or(usize::MAX)
+                    .saturating_add(1)
+            })
+            .min(history.count());
+        let end = start.saturating_add(MAX_HISTORY_PAGE).min(history.count());
+        Ok(history.read(start..end).await?)
     }
 
     async fn last_update(&self) -> u64 {
         self.last_update
     }
 
-    async fn chain_id(&self) -> &linera_base::data_types::ChainId {
-        &self.chain_id
+    /// The chain and application ids of this instance, and when it was created.
+    async fn app_info(&self) -> &AppInfo {
+        &self.app_info
+    }
+}
+
+impl QueryRoot {
+    async fn user_score(&self, user_id: &str) -> async_graphql::Result<Option<UserScore>> {
+        let Some(record) = self.state.scores.get(user_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.to_user_score(user_id.to_string(), &record)))
+    }
+
+    fn to_user_score(&self, user_id: String, record: &ScoreRecord) -> UserScore {
+        UserScore {
+            user_id,
+            score: self.model.decayed(record, self.now),
+            raw_score: record.score,
+            last_activity: record.last_activity,
+        }
+    }
+}
+
+/// Schedules the application's operations. `GetScore` is left out: it only returns a value to
+/// applications calling it.
+pub struct MutationRoot {
+    runtime: Arc<ServiceRuntime<IdentityScoreService>>,
+}
+
+#[Object]
+impl MutationRoot {
+    /// Sets the score of `user_id`. Admin only.
+    async fn update_score(&self, user_id: String, score: f64, reason: String) -> [u8; 0] {
+        self.runtime.schedule_operation(&Operation::UpdateScore {
+            user_id,
+            score,
+            reason,
+        });
+        []
+    }
+
+    /// Claims `user_id` for the signer.
+    async fn register(&self, user_id: String) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::Register { user_id });
+        []
+    }
+
+    /// Records a signal about `user_id` and recomputes their score.
+    async fn record_signal(
+        &self,
+        user_id: String,
+        kind: SignalKind,
+        value: f64,
+        reason: String,
+    ) -> [u8; 0] {
+        self.runtime.schedule_operation(&Operation::RecordSignal {
+            user_id,
+            kind,
+            value,
+            reason,
+        });
+        []
+    }
+
+    /// Attests `claim` about `subject` on behalf of the signer.
+    async fn attest(&self, subject: String, claim: String, expiry: Option<u64>) -> [u8; 0] {
+        self.runtime.schedule_operation(&Operation::Attest {
+            subject,
+            claim,
+            expiry,
+        });
+        []
+    }
+
+    /// Revokes the signer's attestation of `claim` about `subject`.
+    async fn revoke(&self, subject: String, claim: String) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::Revoke { subject, claim });
+        []
+    }
+
+    /// Allows `owner` to record signals that are not self-reported. Admin only.
+    async fn add_signal_issuer(&self, owner: AccountOwner) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::AddSignalIssuer { owner });
+        []
+    }
+
+    /// Revokes `owner`'s right to record signals that are not self-reported. Admin only.
+    async fn remove_signal_issuer(&self, owner: AccountOwner) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::RemoveSignalIssuer { owner });
+        []
     }
 }
@@ -1,9 +1,32 @@
-use linera_sdk::views::{linera_views, RegisterView, RootView, ViewStorageContext};
-use std::collections::HashMap;
+use identity_score::{Attestation, ScoreChange, ScoreRecord, UserSignals};
+use linera_sdk::{
+    linera_base_types::AccountOwner,
+    views::{
+        linera_views, CollectionView, LogView, MapView, RegisterView, RootView, SetView,
+        ViewStorageContext,
+    },
+};
 
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct IdentityScoreState {
-    pub scores: RegisterView<HashMap<String, f64>>,
+    /// Last written score and activity time of each user.
+    pub scores: MapView<String, ScoreRecord>,
     pub last_update: RegisterView<u64>,
+    /// Owner that claimed each registered user id.
+    pub owners: MapView<String, AccountOwner>,
+    /// User id claimed by each registered owner.
+    pub user_ids: MapView<AccountOwner, String>,
+    /// Accumulated signals of each scored user, from which their score is derived.
+    pub signals: MapView<String, UserSignals>,
+    /// Attestations about each subject, including revoked and expired ones.
+    pub attestations: MapView<String, Vec<Attestation>>,
+    /// Every score change of each user, oldest first.
+    pub history: CollectionView<String, LogView<ScoreChange>>,
+    /// Owner allowed to set scores and manage `signal_issuers`.
+    pub admin: RegisterView<Option<AccountOwner>>,
+    /// Owners whose signed operations may record signals that are not self-reported.
+    pub signal_issuers: SetView<AccountOwner>,
+    /// When the application was instantiated, in microseconds.
+    pub created_at: RegisterView<u64>,
 }
@@ -0,0 +1,274 @@
+//! Integration tests for the identity-score application, run against a test validator.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::BTreeSet;
+
+use identity_score::{
+    IdentityScoreAbi, IdentityScoreInstantiation, IdentityScoreParameters, Operation, ScoreModel,
+    SignalKind, SignalWeight,
+};
+use linera_sdk::{
+    bcs,
+    linera_base_types::{AccountOwner, ApplicationId},
+    test::{ActiveChain, TestValidator},
+};
+use serde_json::json;
+
+/// Creates an identity score where each unit of on-chain activity adds one point to a baseline
+/// of 50. The admin defaults to the creator chain's owner.
+async fn create_identity_score(
+    admin: Option<AccountOwner>,
+) -> (TestValidator, ApplicationId<IdentityScoreAbi>, ActiveChain) {
+    TestValidator::with_current_application::<IdentityScoreAbi, _, _>(
+        IdentityScoreParameters {
+            model: ScoreModel {
+                baseline: 50.0,
+                weights: vec![SignalWeight {
+                    kind: SignalKind::OnChainActivity,
+                    weight: 1.0,
+                    decay: 0.0,
+                }],
+                half_life_secs: None,
+                min_score: 0.0,
+                max_score: 100.0,
+            },
+            dashboard: None,
+        },
+        IdentityScoreInstantiation {
+            admin,
+            signal_issuers: BTreeSet::new(),
+        },
+    )
+    .await
+}
+
+async fn execute(
+    chain: &ActiveChain,
+    application_id: ApplicationId<IdentityScoreAbi>,
+    operation: Operation,
+) {
+    chain
+        .add_block(|block| {
+            block.with_operation(application_id, operation);
+        })
+        .await;
+}
+
+/// Checks that the service turns `operation`'s GraphQL mutation into exactly `operation`.
+async fn assert_mutation_schedules(
+    chain: &ActiveChain,
+    application_id: ApplicationId<IdentityScoreAbi>,
+    operation: Operation,
+) {
+    let mutation = operation
+        .to_mutation()
+        .expect("The operation has no mutation");
+    let outcome = chain.graphql_query(application_id, mutation.as_str()).await;
+    let bytes = bcs::to_bytes(&operation).expect("Failed to serialize operation");
+    assert_eq!(
+        serde_json::to_value(&outcome.operations).expect("Failed to serialize operations"),
+        json!([{ "User": { "application_id": application_id.forget_abi(), "bytes": bytes } }]),
+        "{mutation}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_scores_are_recorded_in_the_history() {
+    let (_validator, application_id, chain) = create_identity_score(None).await;
+    for score in [70.0, 65.0] {
+        execute(
+            &chain,
+            application_id,
+            Operation::UpdateScore {
+                user_id: "alice".to_string(),
+                score,
+                reason: "review".to_string(),
+            },
+        )
+        .await;
+    }
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                score(userId: \"alice\") { score } \
+                scoreChanges(userId: \"alice\") { sequence score delta } \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(response["score"]["score"], json!(65.0));
+    assert_eq!(
+        response["scoreChanges"],
+        json!([
+            { "sequence": 0, "score": 70.0, "delta": 70.0 },
+            { "sequence": 1, "score": 65.0, "delta": -5.0 },
+        ])
+    );
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { scoreChanges(userId: \"alice\", after: 0) { sequence } }",
+        )
+        .await
+        .response;
+    assert_eq!(response["scoreChanges"], json!([{ "sequence": 1 }]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scores_from_other_owners_are_ignored() {
+    let (_validator, application_id, chain) =
+        create_identity_score(Some(AccountOwner::from([1; 32]))).await;
+    execute(
+        &chain,
+        application_id,
+        Operation::UpdateScore {
+            user_id: "alice".to_string(),
+            score: 90.0,
+            reason: "self-promotion".to_string(),
+        },
+    )
+    .await;
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { score(userId: \"alice\") { score } }",
+        )
+        .await
+        .response;
+    assert_eq!(response["score"], json!(null));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn registered_owners_report_their_own_activity() {
+    let (_validator, application_id, chain) = create_identity_score(None).await;
+    execute(
+        &chain,
+        application_id,
+        Operation::Register {
+            user_id: "alice".to_string(),
+        },
+    )
+    .await;
+    execute(
+        &chain,
+        application_id,
+        Operation::RecordSignal {
+            user_id: "alice".to_string(),
+            kind: SignalKind::OnChainActivity,
+            value: 5.0,
+            reason: "transactions".to_string(),
+        },
+    )
+    .await;
+    // Penalties are not self-reported, and the owner is not a signal issuer.
+    execute(
+        &chain,
+        application_id,
+        Operation::RecordSignal {
+            user_id: "alice".to_string(),
+            kind: SignalKind::Penalty,
+            value: 1.0,
+            reason: "spam".to_string(),
+        },
+    )
+    .await;
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                score(userId: \"alice\") { score } \
+                scoreChanges(userId: \"alice\") { sequence } \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(response["score"]["score"], json!(55.0));
+    assert_eq!(response["scoreChanges"], json!([{ "sequence": 0 }]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scores_are_paged_by_cursor() {
+    let (_validator, application_id, chain) = create_identity_score(None).await;
+    for user_id in ["alice", "bob", "carol"] {
+        execute(
+            &chain,
+            application_id,
+            Operation::UpdateScore {
+                user_id: user_id.to_string(),
+                score: 60.0,
+                reason: "review".to_string(),
+            },
+        )
+        .await;
+    }
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                first: scores(first: 2) { edges { cursor } pageInfo { hasNextPage } } \
+                rest: scores(after: \"alice\") { edges { node { userId score } } } \
+            }",
+        )
+        .await
+        .response;
+    // User ids are ordered by their serialized form, which starts with their length.
+    assert_eq!(
+        response["first"],
+        json!({
+            "edges": [{ "cursor": "bob" }, { "cursor": "alice" }],
+            "pageInfo": { "hasNextPage": true },
+        })
+    );
+    assert_eq!(
+        response["rest"]["edges"],
+        json!([{ "node": { "userId": "carol", "score": 60.0 } }])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mutations_schedule_their_operations() {
+    let (_validator, application_id, chain) = create_identity_score(None).await;
+    let owner = AccountOwner::from(chain.public_key());
+    let operations = [
+        Operation::UpdateScore {
+            user_id: "alice".to_string(),
+            score: 72.25,
+            reason: "manual \"review\"".to_string(),
+        },
+        Operation::Register {
+            user_id: "alice".to_string(),
+        },
+        Operation::RecordSignal {
+            user_id: "alice".to_string(),
+            kind: SignalKind::Penalty,
+            value: -0.1,
+            reason: "spam".to_string(),
+        },
+        Operation::Attest {
+            subject: "alice".to_string(),
+            claim: "kyc".to_string(),
+            expiry: Some(1_700_000_000_000_000),
+        },
+        Operation::Attest {
+            subject: "alice".to_string(),
+            claim: "kyc".to_string(),
+            expiry: None,
+        },
+        Operation::Revoke {
+            subject: "alice".to_string(),
+            claim: "kyc".to_string(),
+        },
+        Operation::AddSignalIssuer { owner },
+        Operation::RemoveSignalIssuer { owner },
+    ];
+    for operation in operations {
+        assert_mutation_schedules(&chain, application_id, operation).await;
+    }
+}
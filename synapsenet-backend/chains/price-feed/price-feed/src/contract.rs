@@ -3,14 +3,18 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::WithContractAbi,
+    linera_base_types::{AccountOwner, StreamName, WithContractAbi},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
 
-use price_feed::{Operation, PriceFeedEvent};
+use price_feed::{
+    Operation, Price, PriceFeedEvent, PriceFeedInstantiation, PriceFeedParameters,
+    PriceFeedResponse, PricePoint, RoundMetadata, RoundSubmissions, Submission, PRICE_STREAM_NAME,
+};
+use synapsenet_common::dashboard::{self, DashboardAbi, SourceEvent};
 
-use self::state::PriceFeedState;
+use self::state::{PriceFeedState, MAX_HISTORY_PER_TOKEN};
 
 pub struct PriceFeedContract {
     state: PriceFeedState,
@@ -25,8 +29,8 @@ impl WithContractAbi for PriceFeedContract {
 
 impl Contract for PriceFeedContract {
     type Message = ();
-    type Parameters = ();
-    type InstantiationArgument = ();
+    type Parameters = PriceFeedParameters;
+    type InstantiationArgument = PriceFeedInstantiation;
     type EventValue = PriceFeedEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
@@ -36,20 +40,145 @@ impl Contract for PriceFeedContract {
         PriceFeedContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
-        self.runtime.application_parameters();
-        // Chain ID available via self.runtime.chain_id() when needed
+    async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
+        let parameters = self.runtime.application_parameters();
+        assert!(parameters.quorum >= 1, "The quorum must be at least one");
+        let admin = argument
+            .admin
+            .or_else(|| self.runtime.authenticated_signer());
+        self.state.admin.set(admin);
+        for publisher in &argument.publishers {
+            self.state
+                .publishers
+                .insert(publisher)
+                .expect("Failed to add publisher");
+        }
+        self.state
+            .created_at
+            .set(self.runtime.system_time().micros());
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         match operation {
             Operation::UpdatePrice { token, price } => {
+                self.assert_publisher().await;
+                assert!(
+                    self.runtime.application_parameters().quorum <= 1,
+                    "Prices must be submitted per round when the quorum is above one"
+                );
+                self.record_price(token, price, None).await;
+            }
+            Operation::UpdateRound {
+                token,
+                price,
+                round,
+            } => {
+                let publisher = self.assert_publisher().await;
+                let parameters = self.runtime.application_parameters();
                 let timestamp = self.runtime.system_time().micros();
-                self.state.prices.get_mut().push((token.clone(), price));
-                self.state.last_update.set(timestamp);
-                // Event emission handled by framework via EventValue type
+                let prices = self
+                    .state
+                    .prices
+                    .load_entry_mut(&token)
+                    .await
+                    .expect("Failed to load token prices");
+                if let Some(last_round) = prices.last_round.get() {
+                    assert!(
+                        round.round_id > last_round.round_id,
+                        "Round {} for {token} is not newer than stored round {}",
+                        round.round_id,
+                        last_round.round_id
+                    );
+                }
+
+                let round_id = round.round_id;
+                let mut entry = prices
+                    .rounds
+                    .get(&round_id)
+                    .await
+                    .expect("Failed to load round submissions")
+                    .unwrap_or_else(|| RoundSubmissions {
+                        round_id,
+                        submissions: Vec::new(),
+                        committed: None,
+                    });
+                assert!(
+                    entry
+                        .submissions
+                        .iter()
+                        .all(|submission| submission.publisher != publisher),
+                    "{publisher} already submitted round {round_id} for {token}"
+                );
+                entry.submissions.push(Submission {
+                    publisher,
+                    price,
+                    timestamp,
+                });
+                let submitted = entry
+                    .submissions
+                    .iter()
+                    .map(|submission| submission.price)
+                    .collect::<Vec<_>>();
+                entry.committed = parameters.aggregate(&submitted);
+                let committed = entry.committed;
+                prices
+                    .rounds
+                    .insert(&round_id, entry)
+                    .expect("Failed to store round submissions");
+
+                if let Some(price) = committed {
+                    let stale_rounds = prices
+                        .rounds
+                        .indices()
+                        .await
+                        .expect("Failed to read rounds");
+                    for stale_round in stale_rounds.into_iter().filter(|id| *id < round_id) {
+                        prices
+                            .rounds
+                            .remove(&stale_round)
+                            .expect("Failed to prune round");
+                    }
+                    prices.last_round.set(Some(round.clone()));
+                    self.record_price(token, price, Some(round)).await;
+                }
+            }
+            Operation::SetUpdateThresholds { token, thresholds } => {
+                self.assert_admin();
+                match thresholds {
+                    Some(thresholds) => self
+                        .state
+                        .thresholds
+                        .insert(&token, thresholds)
+                        .expect("Failed to store update thresholds"),
+                    None => self
+                        .state
+                        .thresholds
+                        .remove(&token)
+                        .expect("Failed to clear update thresholds"),
+                }
+            }
+            Operation::AddPublisher { owner } => {
+                self.assert_admin();
+                self.state
+                    .publishers
+                    .insert(&owner)
+                    .expect("Failed to add publisher");
+            }
+            Operation::RemovePublisher { owner } => {
+                self.assert_admin();
+                self.state
+                    .publishers
+                    .remove(&owner)
+                    .expect("Failed to remove publisher");
+            }
+            Operation::ReadEvent { chain_id, index } => {
+                let event =
+                    self.runtime
+                        .read_event(chain_id, StreamName::from(PRICE_STREAM_NAME), index);
+                return PriceFeedResponse::Event(event);
             }
         }
+        PriceFeedResponse::Ok
     }
 
     async fn execute_message(&mut self, _message: Self::Message) {}
@@ -58,3 +187,95 @@ impl Contract for PriceFeedContract {
         self.state.save().await.expect("Failed to save state");
     }
 }
+
+impl PriceFeedContract {
+    /// Returns the signer of the current operation, which must be present.
+    fn signer(&mut self) -> AccountOwner {
+        self.runtime
+            .authenticated_signer()
+            .expect("Operation must be signed")
+    }
+
+    /// Rejects the operation unless it was signed by an authorized publisher, returning the
+    /// publisher.
+    async fn assert_publisher(&mut self) -> AccountOwner {
+        let signer = self.signer();
+        let authorized = self
+            .state
+            .publishers
+            .contains(&signer)
+            .await
+            .expect("Failed to read publishers");
+        assert!(authorized, "{signer} is not an authorized price publisher");
+        signer
+    }
+
+    /// Rejects the operation unless it was signed by the admin.
+    fn assert_admin(&mut self) {
+        let signer = self.signer();
+        assert_eq!(
+            self.state.admin.get().as_ref(),
+            Some(&signer),
+            "Only the admin can change the price-feed configuration"
+        );
+    }
+
+    /// Makes `price` the latest price of `token`, appends it to the token's history and emits it
+    /// on the price stream, unless the token's thresholds say it is not due yet.
+    async fn record_price(&mut self, token: String, price: Price, round: Option<RoundMetadata>) {
+        let timestamp = self.runtime.system_time().micros();
+        let thresholds = self
+            .state
+            .thresholds
+            .get(&token)
+            .await
+            .expect("Failed to read update thresholds");
+        let prices = self
+            .state
+            .prices
+            .load_entry_mut(&token)
+            .await
+            .expect("Failed to load token prices");
+        if let (Some(thresholds), Some(latest)) = (thresholds, prices.latest.get()) {
+            if !thresholds.is_due(latest, price, timestamp) {
+                *prices.coalesced.get_mut() += 1;
+                return;
+            }
+        }
+        let point = PricePoint {
+            price,
+            round: round.clone(),
+            timestamp,
+        };
+        prices.latest.set(Some(point.clone()));
+        prices.history.push_back(point);
+        if prices.history.count() > MAX_HISTORY_PER_TOKEN {
+            prices.history.delete_front();
+        }
+        self.state.last_update.set(timestamp);
+        let event = PriceFeedEvent::PriceUpdate {
+            token,
+            price,
+            round,
+            timestamp,
+        };
+        let index = self
+            .runtime
+            .emit(StreamName::from(PRICE_STREAM_NAME), &event);
+        self.forward_to_dashboard(event, index);
+    }
+
+    /// Forwards `event`, emitted at `index` of the price stream, to the configured dashboard
+    /// application, which relays it to the dashboard chain.
+    fn forward_to_dashboard(&mut self, event: PriceFeedEvent, index: u32) {
+        let Some(dashboard) = self.runtime.application_parameters().dashboard else {
+            return;
+        };
+        let operation = dashboard::Operation::Forward {
+            event: SourceEvent::Price(event),
+            index,
+        };
+        self.runtime
+            .call_application(true, dashboard.with_abi::<DashboardAbi>(), &operation);
+    }
+}
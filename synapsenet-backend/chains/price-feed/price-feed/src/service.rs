@@ -2,19 +2,22 @@
 
 mod state;
 
-use self::state::PriceFeedState;
+use self::state::{PriceFeedState, MAX_HISTORY_PER_TOKEN};
 use async_graphql::{EmptySubscription, Object, Schema};
 use linera_sdk::{
-    base::WithServiceAbi,
+    linera_base_types::{AccountOwner, WithServiceAbi},
     views::View,
     Service, ServiceRuntime,
 };
+use price_feed::{
+    AppInfo, Operation, Price, PriceFeedAbi, PriceFeedParameters, PricePoint, RoundId,
+    RoundMetadata, RoundSubmissions, UpdateThresholds,
+};
 use std::sync::Arc;
-use price_feed::PriceFeedAbi;
 
 pub struct PriceFeedService {
-    state: PriceFeedState,
-    runtime: ServiceRuntime<Self>,
+    state: Arc<PriceFeedState>,
+    runtime: Arc<ServiceRuntime<Self>>,
 }
 
 linera_sdk::service!(PriceFeedService);
@@ -24,52 +27,213 @@ impl WithServiceAbi for PriceFeedService {
 }
 
 impl Service for PriceFeedService {
-    type Parameters = ();
+    type Parameters = PriceFeedParameters;
 
-    async fn load(runtime: ServiceRuntime<Self>) -> Self {
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = PriceFeedState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
-        PriceFeedService { state, runtime }
+        PriceFeedService {
+            state: Arc::new(state),
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    async fn handle_query(&self, request: Self::Query) -> Self::QueryResponse {
+        self.schema().execute(request).await
     }
+}
+
+type PriceFeedSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
 
-    async fn graphql_query(&mut self, request: Self::Query) -> Self::QueryResponse {
-        let schema = Schema::build(
+impl PriceFeedService {
+    fn schema(&self) -> PriceFeedSchema {
+        Schema::build(
             QueryRoot {
-                prices: self.state.prices.get().clone(),
+                state: self.state.clone(),
+                parameters: self.runtime.application_parameters(),
                 last_update: *self.state.last_update.get(),
-                chain_id: *self.state.chain_id.get(),
+                app_info: AppInfo {
+                    chain_id: self.runtime.chain_id(),
+                    application_id: self.runtime.application_id().forget_abi(),
+                    creator_chain_id: self.runtime.application_creator_chain_id(),
+                    created_at: *self.state.created_at.get(),
+                },
+            },
+            MutationRoot {
+                runtime: self.runtime.clone(),
             },
-            price_feed::Operation::mutation_root(),
             EmptySubscription,
         )
-        .finish();
-        schema.execute(request).await
-    }
-
-    async fn graphql_subscription(&mut self, _request: Self::Query) -> Self::QueryResponse {
-        // This is not supported in this example.
-        unimplemented!("GraphQL subscriptions are not supported by the PriceFeed application")
+        .finish()
     }
 }
 
 pub struct QueryRoot {
-    prices: Vec<(String, f64)>,
+    state: Arc<PriceFeedState>,
+    parameters: PriceFeedParameters,
     last_update: u64,
-    chain_id: linera_base::data_types::ChainId,
+    app_info: AppInfo,
 }
 
 #[Object]
 impl QueryRoot {
-    async fn prices(&self) -> &Vec<(String, f64)> {
-        &self.prices
+    /// Quorum and outlier settings used to aggregate publisher submissions.
+    async fn parameters(&self) -> &PriceFeedParameters {
+        &self.parameters
+    }
+
+    /// Owner allowed to add and remove publishers.
+    async fn admin(&self) -> Option<AccountOwner> {
+        *self.state.admin.get()
+    }
+
+    /// Owners currently allowed to publish prices.
+    async fn publishers(&self) -> async_graphql::Result<Vec<AccountOwner>> {
+        Ok(self.state.publishers.indices().await?)
+    }
+
+    /// Tokens that have at least one recorded price.
+    async fn tokens(&self) -> async_graphql::Result<Vec<String>> {
+        Ok(self.state.prices.indices().await?)
+    }
+
+    /// The most recent price recorded for `token`.
+    async fn latest_price(&self, token: String) -> async_graphql::Result<Option<PricePoint>> {
+        let Some(prices) = self.state.prices.try_load_entry(&token).await? else {
+            return Ok(None);
+        };
+        Ok(prices.latest.get().clone())
+    }
+
+    /// The thresholds deciding which prices of `token` are committed, if any.
+    async fn update_thresholds(
+        &self,
+        token: String,
+    ) -> async_graphql::Result<Option<UpdateThresholds>> {
+        Ok(self.state.thresholds.get(&token).await?)
+    }
+
+    /// Number of prices of `token` dropped for not being due under its thresholds.
+    async fn coalesced_updates(&self, token: String) -> async_graphql::Result<u64> {
+        let Some(prices) = self.state.prices.try_load_entry(&token).await? else {
+            return Ok(0);
+        };
+        Ok(*prices.coalesced.get())
+    }
+
+    /// The newest oracle round committed for `token`.
+    async fn latest_round(&self, token: String) -> async_graphql::Result<Option<RoundMetadata>> {
+        let Some(prices) = self.state.prices.try_load_entry(&token).await? else {
+            return Ok(None);
+        };
+        Ok(prices.last_round.get().clone())
+    }
+
+    /// Publisher submissions for `token`, either for one round or for every round still kept:
+    /// the last committed round and any newer rounds awaiting quorum.
+    async fn round_submissions(
+        &self,
+        token: String,
+        round_id: Option<RoundId>,
+    ) -> async_graphql::Result<Vec<RoundSubmissions>> {
+        let Some(prices) = self.state.prices.try_load_entry(&token).await? else {
+            return Ok(Vec::new());
+        };
+        if let Some(round_id) = round_id {
+            return Ok(prices.rounds.get(&round_id).await?.into_iter().collect());
+        }
+        let mut rounds = Vec::new();
+        prices
+            .rounds
+            .for_each_index_value(|_, round| {
+                rounds.push(round.into_owned());
+                Ok(())
+            })
+            .await?;
+        rounds.sort_by_key(|round| round.round_id);
+        Ok(rounds)
+    }
+
+    /// Recent prices for `token`, newest first, optionally restricted to points at or after
+    /// `since` (microseconds).
+    async fn price_history(
+        &self,
+        token: String,
+        limit: Option<usize>,
+        since: Option<u64>,
+    ) -> async_graphql::Result<Vec<PricePoint>> {
+        let Some(prices) = self.state.prices.try_load_entry(&token).await? else {
+            return Ok(Vec::new());
+        };
+        let limit = limit
+            .unwrap_or(MAX_HISTORY_PER_TOKEN)
+            .min(MAX_HISTORY_PER_TOKEN);
+        let mut points = prices.history.read_back(limit).await?;
+        if let Some(since) = since {
+            points.retain(|point| point.timestamp >= since);
+        }
+        points.reverse();
+        Ok(points)
     }
 
     async fn last_update(&self) -> u64 {
         self.last_update
     }
 
-    async fn chain_id(&self) -> &linera_base::data_types::ChainId {
-        &self.chain_id
+    /// The chain and application ids of this instance, and when it was created.
+    async fn app_info(&self) -> &AppInfo {
+        &self.app_info
+    }
+}
+
+/// Schedules the application's operations. `ReadEvent` is left out: it only returns a value to
+/// applications calling it.
+pub struct MutationRoot {
+    runtime: Arc<ServiceRuntime<PriceFeedService>>,
+}
+
+#[Object]
+impl MutationRoot {
+    /// Publishes the signer's price for `token`. Publishers only.
+    async fn update_price(&self, token: String, price: Price) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::UpdatePrice { token, price });
+        []
+    }
+
+    /// Submits the signer's answer for an oracle round of `token`. Publishers only.
+    async fn update_round(&self, token: String, price: Price, round: RoundMetadata) -> [u8; 0] {
+        self.runtime.schedule_operation(&Operation::UpdateRound {
+            token,
+            price,
+            round,
+        });
+        []
+    }
+
+    /// Allows `owner` to publish prices. Admin only.
+    async fn add_publisher(&self, owner: AccountOwner) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::AddPublisher { owner });
+        []
+    }
+
+    /// Revokes `owner`'s right to publish prices. Admin only.
+    async fn remove_publisher(&self, owner: AccountOwner) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::RemovePublisher { owner });
+        []
+    }
+
+    /// Sets or clears the thresholds deciding which prices of `token` are committed. Admin only.
+    async fn set_update_thresholds(
+        &self,
+        token: String,
+        thresholds: Option<UpdateThresholds>,
+    ) -> [u8; 0] {
+        self.runtime
+            .schedule_operation(&Operation::SetUpdateThresholds { token, thresholds });
+        []
     }
 }
@@ -1,8 +1,42 @@
-use linera_sdk::views::{linera_views, RegisterView, RootView, ViewStorageContext};
+use linera_sdk::{
+    linera_base_types::AccountOwner,
+    views::{
+        linera_views, CollectionView, MapView, QueueView, RegisterView, RootView, SetView, View,
+        ViewStorageContext,
+    },
+};
+use price_feed::{PricePoint, RoundId, RoundMetadata, RoundSubmissions, UpdateThresholds};
+
+/// Number of points kept in each token's history before the oldest are dropped.
+pub const MAX_HISTORY_PER_TOKEN: usize = 1_000;
 
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct PriceFeedState {
-    pub prices: RegisterView<Vec<(String, f64)>>,
+    pub prices: CollectionView<String, TokenPrices>,
+    /// Per-token thresholds. Prices of a token with thresholds that are not due under them are
+    /// not stored.
+    pub thresholds: MapView<String, UpdateThresholds>,
     pub last_update: RegisterView<u64>,
+    /// Owner allowed to manage `publishers`.
+    pub admin: RegisterView<Option<AccountOwner>>,
+    /// Owners whose signed operations may update prices.
+    pub publishers: SetView<AccountOwner>,
+    /// When the application was instantiated, in microseconds.
+    pub created_at: RegisterView<u64>,
+}
+
+/// Price data for a single token, loaded independently of every other token.
+#[derive(View)]
+#[view(context = ViewStorageContext)]
+pub struct TokenPrices {
+    pub latest: RegisterView<Option<PricePoint>>,
+    /// Number of prices dropped because they were not due under the token's thresholds.
+    pub coalesced: RegisterView<u64>,
+    /// The newest oracle round committed for this token.
+    pub last_round: RegisterView<Option<RoundMetadata>>,
+    /// Submissions for the last committed round and any newer rounds still awaiting quorum.
+    pub rounds: MapView<RoundId, RoundSubmissions>,
+    /// Oldest first, bounded by [`MAX_HISTORY_PER_TOKEN`].
+    pub history: QueueView<PricePoint>,
 }
@@ -0,0 +1,339 @@
+//! Integration tests for the price-feed application, run against a test validator.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::BTreeSet;
+
+use linera_sdk::{
+    bcs,
+    linera_base_types::{AccountOwner, AccountSecretKey, ApplicationId},
+    test::{ActiveChain, TestValidator},
+};
+use price_feed::{
+    Operation, Price, PriceFeedAbi, PriceFeedInstantiation, PriceFeedParameters, RoundId,
+    RoundMetadata, UpdateThresholds,
+};
+use serde_json::json;
+
+/// Creates a price feed without publishers, whose admin is the creator chain's owner.
+async fn create_price_feed(
+    quorum: u32,
+) -> (TestValidator, ApplicationId<PriceFeedAbi>, ActiveChain) {
+    TestValidator::with_current_application::<PriceFeedAbi, _, _>(
+        PriceFeedParameters {
+            quorum,
+            max_deviation_bps: 500,
+            dashboard: None,
+        },
+        PriceFeedInstantiation {
+            admin: None,
+            publishers: BTreeSet::new(),
+        },
+    )
+    .await
+}
+
+/// Lets the chain's owner publish prices.
+async fn add_publisher(chain: &ActiveChain, application_id: ApplicationId<PriceFeedAbi>) {
+    add_other_publisher(
+        chain,
+        application_id,
+        AccountOwner::from(chain.public_key()),
+    )
+    .await;
+}
+
+/// Lets `owner` publish prices.
+async fn add_other_publisher(
+    chain: &ActiveChain,
+    application_id: ApplicationId<PriceFeedAbi>,
+    owner: AccountOwner,
+) {
+    chain
+        .add_block(|block| {
+            block.with_operation(application_id, Operation::AddPublisher { owner });
+        })
+        .await;
+}
+
+async fn update_price(
+    chain: &ActiveChain,
+    application_id: ApplicationId<PriceFeedAbi>,
+    token: &str,
+    value: i128,
+) {
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::UpdatePrice {
+                    token: token.to_string(),
+                    price: Price::new(value, 2),
+                },
+            );
+        })
+        .await;
+}
+
+/// Submits the signer's answer for `round_id` of ETH, returning whether the block was accepted.
+async fn update_round(
+    chain: &ActiveChain,
+    application_id: ApplicationId<PriceFeedAbi>,
+    round_id: u128,
+    value: i128,
+) -> bool {
+    let operation = Operation::UpdateRound {
+        token: "ETH".to_string(),
+        price: Price::new(value, 8),
+        round: RoundMetadata {
+            round_id: RoundId(round_id),
+            answered_in_round: RoundId(round_id),
+            started_at: 0,
+            updated_at: 0,
+            network: "Polygon Amoy".to_string(),
+        },
+    };
+    chain
+        .try_add_block(|block| {
+            block.with_operation(application_id, operation);
+        })
+        .await
+        .is_ok()
+}
+
+/// Checks that the service turns `operation`'s GraphQL mutation into exactly `operation`.
+async fn assert_mutation_schedules(
+    chain: &ActiveChain,
+    application_id: ApplicationId<PriceFeedAbi>,
+    operation: Operation,
+) {
+    let mutation = operation
+        .to_mutation()
+        .expect("The operation has no mutation");
+    let outcome = chain.graphql_query(application_id, mutation.as_str()).await;
+    let bytes = bcs::to_bytes(&operation).expect("Failed to serialize operation");
+    assert_eq!(
+        serde_json::to_value(&outcome.operations).expect("Failed to serialize operations"),
+        json!([{ "User": { "application_id": application_id.forget_abi(), "bytes": bytes } }]),
+        "{mutation}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn published_prices_are_queryable() {
+    let (_validator, application_id, chain) = create_price_feed(1).await;
+    add_publisher(&chain, application_id).await;
+    update_price(&chain, application_id, "ETH", 250_000).await;
+    update_price(&chain, application_id, "ETH", 251_000).await;
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                latestPrice(token: \"ETH\") { price } \
+                priceHistory(token: \"ETH\") { price } \
+                tokens \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(
+        response["latestPrice"]["price"],
+        json!({ "value": "251000", "decimals": 2 })
+    );
+    assert_eq!(
+        response["priceHistory"],
+        json!([
+            { "price": { "value": "251000", "decimals": 2 } },
+            { "price": { "value": "250000", "decimals": 2 } },
+        ])
+    );
+    assert_eq!(response["tokens"], json!(["ETH"]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prices_from_owners_that_are_not_publishers_are_rejected() {
+    let (_validator, application_id, chain) = create_price_feed(1).await;
+
+    let result = chain
+        .try_add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::UpdatePrice {
+                    token: "ETH".to_string(),
+                    price: Price::new(250_000, 2),
+                },
+            );
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prices_that_are_not_due_are_coalesced() {
+    let (_validator, application_id, chain) = create_price_feed(1).await;
+    add_publisher(&chain, application_id).await;
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::SetUpdateThresholds {
+                    token: "ETH".to_string(),
+                    thresholds: Some(UpdateThresholds {
+                        deviation_bps: 100,
+                        heartbeat_secs: 3_600,
+                    }),
+                },
+            );
+        })
+        .await;
+    update_price(&chain, application_id, "ETH", 250_000).await;
+    // Within the deviation and the heartbeat of the previous price.
+    update_price(&chain, application_id, "ETH", 250_100).await;
+    // Beyond the deviation.
+    update_price(&chain, application_id, "ETH", 260_000).await;
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                coalescedUpdates(token: \"ETH\") \
+                latestPrice(token: \"ETH\") { price } \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(response["coalescedUpdates"], json!(1));
+    assert_eq!(
+        response["latestPrice"]["price"],
+        json!({ "value": "260000", "decimals": 2 })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rounds_that_are_not_newer_are_rejected() {
+    let (_validator, application_id, chain) = create_price_feed(1).await;
+    add_publisher(&chain, application_id).await;
+    assert!(update_round(&chain, application_id, 2, 250_000_000_000).await);
+    assert!(!update_round(&chain, application_id, 2, 251_000_000_000).await);
+    assert!(!update_round(&chain, application_id, 1, 252_000_000_000).await);
+    assert!(update_round(&chain, application_id, 3, 253_000_000_000).await);
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                latestRound(token: \"ETH\") { roundId } \
+                priceHistory(token: \"ETH\") { price } \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(response["latestRound"]["roundId"], json!("3"));
+    assert_eq!(
+        response["priceHistory"],
+        json!([
+            { "price": { "value": "253000000000", "decimals": 8 } },
+            { "price": { "value": "250000000000", "decimals": 8 } },
+        ])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rounds_are_committed_once_a_quorum_of_publishers_submitted() {
+    let (_validator, application_id, mut chain) = create_price_feed(2).await;
+    let second_publisher = AccountSecretKey::generate();
+    add_publisher(&chain, application_id).await;
+    add_other_publisher(
+        &chain,
+        application_id,
+        AccountOwner::from(second_publisher.public()),
+    )
+    .await;
+    // Round 1 never reaches the quorum.
+    assert!(update_round(&chain, application_id, 1, 250_000_000_000).await);
+    assert!(update_round(&chain, application_id, 2, 250_000_000_000).await);
+    // Each publisher counts once per round.
+    assert!(!update_round(&chain, application_id, 2, 260_000_000_000).await);
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                latestPrice(token: \"ETH\") { price } \
+                roundSubmissions(token: \"ETH\") { roundId committed } \
+            }",
+        )
+        .await
+        .response;
+    assert_eq!(response["latestPrice"], json!(null));
+    assert_eq!(
+        response["roundSubmissions"],
+        json!([
+            { "roundId": "1", "committed": null },
+            { "roundId": "2", "committed": null },
+        ])
+    );
+
+    chain.set_key_pair(second_publisher);
+    assert!(update_round(&chain, application_id, 2, 252_000_000_000).await);
+
+    let response = chain
+        .graphql_query(
+            application_id,
+            "query { \
+                latestPrice(token: \"ETH\") { price } \
+                latestRound(token: \"ETH\") { roundId } \
+                roundSubmissions(token: \"ETH\") { roundId committed } \
+            }",
+        )
+        .await
+        .response;
+    let median = json!({ "value": "251000000000", "decimals": 8 });
+    assert_eq!(response["latestPrice"]["price"], median);
+    assert_eq!(response["latestRound"]["roundId"], json!("2"));
+    // Committing round 2 pruned the older round 1.
+    assert_eq!(
+        response["roundSubmissions"],
+        json!([{ "roundId": "2", "committed": median }])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mutations_schedule_their_operations() {
+    let (_validator, application_id, chain) = create_price_feed(1).await;
+    let owner = AccountOwner::from(chain.public_key());
+    let operations = [
+        Operation::UpdatePrice {
+            token: "ETH".to_string(),
+            price: Price::new(301_245_000_000, 8),
+        },
+        Operation::UpdateRound {
+            token: "ETH \"quoted\"".to_string(),
+            price: Price::new(-5, 2),
+            round: RoundMetadata {
+                round_id: RoundId(u128::from(u64::MAX) + 1),
+                answered_in_round: RoundId(7),
+                started_at: 1_700_000_000,
+                updated_at: 1_700_000_060,
+                network: "Polygon Amoy".to_string(),
+            },
+        },
+        Operation::AddPublisher { owner },
+        Operation::RemovePublisher { owner },
+        Operation::SetUpdateThresholds {
+            token: "ETH".to_string(),
+            thresholds: Some(UpdateThresholds {
+                deviation_bps: 50,
+                heartbeat_secs: 3_600,
+            }),
+        },
+        Operation::SetUpdateThresholds {
+            token: "ETH".to_string(),
+            thresholds: None,
+        },
+    ];
+    for operation in operations {
+        assert_mutation_schedules(&chain, application_id, operation).await;
+    }
+}
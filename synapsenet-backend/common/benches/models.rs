@@ -0,0 +1,61 @@
+//! Benchmarks of the computations the contracts run on every price round and score signal.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use synapsenet_common::{
+    identity_score::{ScoreModel, SignalKind, SignalWeight, UserSignals},
+    price_feed::PriceFeedParameters,
+    Price,
+};
+
+fn aggregate(c: &mut Criterion) {
+    let parameters = PriceFeedParameters {
+        quorum: 3,
+        max_deviation_bps: 100,
+        dashboard: None,
+    };
+    let mut group = c.benchmark_group("aggregate");
+    for publishers in [3, 11, 51] {
+        let prices = (0..publishers)
+            .map(|publisher| Price::new(300_000_000_000 + publisher * 7_919 % 1_000_000, 8))
+            .collect::<Vec<_>>();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(publishers),
+            &prices,
+            |b, prices| b.iter(|| parameters.aggregate(black_box(prices))),
+        );
+    }
+    group.finish();
+}
+
+fn score(c: &mut Criterion) {
+    let model = ScoreModel {
+        baseline: 50.0,
+        weights: [
+            (SignalKind::OnChainActivity, 0.5),
+            (SignalKind::Attestation, 5.0),
+            (SignalKind::Penalty, -10.0),
+        ]
+        .into_iter()
+        .map(|(kind, weight)| SignalWeight {
+            kind,
+            weight,
+            decay: 0.1,
+        })
+        .collect(),
+        half_life_secs: Some(30 * 24 * 3_600),
+        min_score: 0.0,
+        max_score: 100.0,
+    };
+    c.bench_function("accumulate_and_score", |b| {
+        b.iter(|| {
+            let mut signals = UserSignals::default();
+            model.decay_signals(&mut signals, black_box(3_600_000_000));
+            model.accumulate(&mut signals, SignalKind::OnChainActivity, black_box(3.0));
+            model.accumulate(&mut signals, SignalKind::Attestation, black_box(1.0));
+            model.settle_score(&mut signals)
+        })
+    });
+}
+
+criterion_group!(benches, aggregate, score);
+criterion_main!(benches);
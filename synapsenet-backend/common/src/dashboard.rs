@@ -0,0 +1,515 @@
+//! ABI and types of the dashboard application.
+
+use async_graphql::{ComplexObject, Enum, InputType, Json, Request, Response, SimpleObject};
+use linera_sdk::linera_base_types::{
+    AccountOwner, ApplicationId, ChainId, ContractAbi, ServiceAbi,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    graphql::mutation,
+    identity_score::{IdentityScoreEvent, SCORE_STREAM_NAME},
+    price_feed::{PriceFeedEvent, PRICE_STREAM_NAME},
+    Price, Score, UserId,
+};
+
+pub struct DashboardAbi;
+
+impl ContractAbi for DashboardAbi {
+    type Operation = Operation;
+    type Response = ();
+}
+
+impl ServiceAbi for DashboardAbi {
+    type Query = Request;
+    type QueryResponse = Response;
+}
+
+/// The dashboard service exposes every operation except `Forward` as a mutation.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Operation {
+    /// Records an event observed on another chain. `payload` must be a JSON document, or the
+    /// event is only counted as invalid, and `emitted_at` is the source's timestamp in
+    /// microseconds. Admin only.
+    ReceiveEvent {
+        event_type: DashboardEventType,
+        source_chain_id: ChainId,
+        source_application_id: Option<ApplicationId>,
+        payload: String,
+        emitted_at: u64,
+    },
+    /// Sends an event to the dashboard chain given in the [`DashboardParameters`]. Only
+    /// callable by other applications, through `call_application`, whose id is recorded as the
+    /// event's source. `index` is the event's position in the source's event stream, so that
+    /// events also read from the stream are only recorded once.
+    Forward { event: SourceEvent, index: u32 },
+    /// Accepts events forwarded by `application_id`. Admin only.
+    AddSource { application_id: ApplicationId },
+    /// Stops accepting events forwarded by `application_id`. Admin only.
+    RemoveSource { application_id: ApplicationId },
+    /// Reads the events of `event_type` that `application_id` emits on `chain_id` from its
+    /// event stream, without an off-chain relay. `application_id` must be an accepted source.
+    /// Admin only.
+    Subscribe {
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        event_type: DashboardEventType,
+    },
+    /// Stops reading the event stream of a previous `Subscribe`. Admin only.
+    Unsubscribe {
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        event_type: DashboardEventType,
+    },
+}
+
+impl Operation {
+    /// The GraphQL mutation scheduling this operation through the application's service, or
+    /// `None` for `Forward`, which is only available through `call_application`.
+    pub fn to_mutation(&self) -> Option<String> {
+        let mutation = match self {
+            Operation::ReceiveEvent {
+                event_type,
+                source_chain_id,
+                source_application_id,
+                payload,
+                emitted_at,
+            } => mutation(
+                "receiveEvent",
+                &[
+                    ("eventType", event_type.to_value()),
+                    ("sourceChainId", source_chain_id.to_value()),
+                    ("sourceApplicationId", source_application_id.to_value()),
+                    ("payload", payload.to_value()),
+                    ("emittedAt", emitted_at.to_value()),
+                ],
+            ),
+            Operation::Forward { .. } => return None,
+            Operation::AddSource { application_id } => {
+                mutation("addSource", &[("applicationId", application_id.to_value())])
+            }
+            Operation::RemoveSource { application_id } => mutation(
+                "removeSource",
+                &[("applicationId", application_id.to_value())],
+            ),
+            Operation::Subscribe {
+                chain_id,
+                application_id,
+                event_type,
+            } => mutation(
+                "subscribe",
+                &[
+                    ("chainId", chain_id.to_value()),
+                    ("applicationId", application_id.to_value()),
+                    ("eventType", event_type.to_value()),
+                ],
+            ),
+            Operation::Unsubscribe {
+                chain_id,
+                application_id,
+                event_type,
+            } => mutation(
+                "unsubscribe",
+                &[
+                    ("chainId", chain_id.to_value()),
+                    ("applicationId", application_id.to_value()),
+                    ("eventType", event_type.to_value()),
+                ],
+            ),
+        };
+        Some(mutation)
+    }
+}
+
+/// Messages sent between instances of the dashboard on different chains.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Message {
+    /// An event forwarded by `source_application_id` on the sending chain, emitted at `index`
+    /// of its event stream.
+    Event {
+        source_application_id: ApplicationId,
+        event: SourceEvent,
+        index: u32,
+    },
+}
+
+/// An event of one of the applications whose events the dashboard aggregates.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum SourceEvent {
+    Price(PriceFeedEvent),
+    Score(IdentityScoreEvent),
+}
+
+impl SourceEvent {
+    pub fn event_type(&self) -> DashboardEventType {
+        match self {
+            SourceEvent::Price(_) => DashboardEventType::PriceUpdate,
+            SourceEvent::Score(_) => DashboardEventType::ScoreUpdate,
+        }
+    }
+
+    /// When the source application emitted the event, in microseconds.
+    pub fn emitted_at(&self) -> u64 {
+        match self {
+            SourceEvent::Price(PriceFeedEvent::PriceUpdate { timestamp, .. })
+            | SourceEvent::Score(IdentityScoreEvent::ScoreUpdate { timestamp, .. }) => *timestamp,
+        }
+    }
+
+    /// Decodes the JSON `payload` of an event of `event_type`, if it is one of the source
+    /// applications' events.
+    pub fn from_json(event_type: DashboardEventType, payload: &str) -> Option<Self> {
+        match event_type {
+            DashboardEventType::PriceUpdate => {
+                serde_json::from_str(payload).ok().map(SourceEvent::Price)
+            }
+            DashboardEventType::ScoreUpdate => {
+                serde_json::from_str(payload).ok().map(SourceEvent::Score)
+            }
+            DashboardEventType::Other => None,
+        }
+    }
+
+    /// The event as the JSON document stored in its [`EventRecord`].
+    pub fn to_json(&self) -> String {
+        match self {
+            SourceEvent::Price(event) => serde_json::to_string(event),
+            SourceEvent::Score(event) => serde_json::to_string(event),
+        }
+        .expect("Failed to serialize event")
+    }
+}
+
+/// Argument used when creating the dashboard application.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DashboardInstantiation {
+    /// Owner allowed to manage event sources. Defaults to the signer creating the application.
+    pub admin: Option<AccountOwner>,
+    /// Applications whose forwarded events are accepted from the start.
+    pub sources: Vec<ApplicationId>,
+}
+
+/// Settings fixed when the dashboard application is created.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct DashboardParameters {
+    /// Chain on which events are aggregated. Sources on other chains forward their events
+    /// there.
+    pub chain_id: ChainId,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+/// Which received events the dashboard keeps. Older events are evicted once either limit is
+/// exceeded; unset limits do not apply.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct RetentionPolicy {
+    pub max_events: Option<u64>,
+    pub max_age_secs: Option<u64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            max_events: Some(10_000),
+            max_age_secs: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Whether `record` must be evicted at `now` (microseconds), given that `count` events are
+    /// currently kept.
+    pub fn evicts(&self, record: &EventRecord, count: usize, now: u64) -> bool {
+        let too_many = self
+            .max_events
+            .is_some_and(|max_events| count as u64 > max_events);
+        let too_old = self.max_age_secs.is_some_and(|max_age_secs| {
+            now.saturating_sub(record.received_at) > max_age_secs.saturating_mul(1_000_000)
+        });
+        too_many || too_old
+    }
+}
+
+/// Name of the event stream on which the dashboard announces every event it records, as a
+/// [`DashboardEvent`].
+pub const DASHBOARD_STREAM_NAME: &[u8] = b"events";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum DashboardEvent {
+    EventReceived {
+        sequence: u64,
+        event_type: DashboardEventType,
+        /// When the dashboard recorded the event, in microseconds.
+        timestamp: u64,
+    },
+}
+
+/// The kinds of events the dashboard aggregates.
+#[derive(
+    Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize, Enum,
+)]
+pub enum DashboardEventType {
+    PriceUpdate,
+    ScoreUpdate,
+    Other,
+}
+
+impl DashboardEventType {
+    /// Name of the event stream on which events of this type are emitted, if any.
+    pub fn stream_name(self) -> Option<&'static [u8]> {
+        match self {
+            DashboardEventType::PriceUpdate => Some(PRICE_STREAM_NAME),
+            DashboardEventType::ScoreUpdate => Some(SCORE_STREAM_NAME),
+            DashboardEventType::Other => None,
+        }
+    }
+
+    /// The type of the events emitted on the stream called `stream_name`, if any.
+    pub fn from_stream_name(stream_name: &[u8]) -> Option<Self> {
+        match stream_name {
+            PRICE_STREAM_NAME => Some(DashboardEventType::PriceUpdate),
+            SCORE_STREAM_NAME => Some(DashboardEventType::ScoreUpdate),
+            _ => None,
+        }
+    }
+}
+
+/// An event stored by the dashboard.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+#[graphql(complex)]
+pub struct EventRecord {
+    /// Position of the event among all events received by the dashboard, starting at zero.
+    pub sequence: u64,
+    pub event_type: DashboardEventType,
+    pub source_chain_id: ChainId,
+    pub source_application_id: Option<ApplicationId>,
+    /// The event's JSON document, exposed through the `payload` resolver.
+    #[graphql(skip)]
+    pub payload: String,
+    /// When the source emitted the event, in microseconds.
+    pub emitted_at: u64,
+    /// When the dashboard recorded the event, in microseconds.
+    pub received_at: u64,
+}
+
+#[ComplexObject]
+impl EventRecord {
+    /// The event's JSON document.
+    async fn payload(&self) -> async_graphql::Result<Json<serde_json::Value>> {
+        Ok(Json(serde_json::from_str(&self.payload)?))
+    }
+}
+
+/// An event stream the dashboard reads.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct StreamSubscription {
+    pub chain_id: ChainId,
+    pub application_id: ApplicationId,
+    pub event_type: DashboardEventType,
+}
+
+/// Number of events of one type recorded by the dashboard, including evicted ones.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct EventCount {
+    pub event_type: DashboardEventType,
+    pub count: u64,
+}
+
+/// The most recent price of a token seen by the dashboard.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct LatestPrice {
+    pub token: String,
+    pub price: Price,
+    /// When the price feed committed the price, in microseconds.
+    pub timestamp: u64,
+}
+
+/// The most recent score of a user seen by the dashboard.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct LatestScore {
+    pub user_id: UserId,
+    #[serde(with = "crate::float_bits")]
+    pub score: Score,
+    /// When the identity-score application set the score, in microseconds.
+    pub timestamp: u64,
+}
+
+/// Bucket sizes for which the dashboard keeps candles and event rates.
+#[derive(
+    Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize, Enum,
+)]
+pub enum CandleInterval {
+    Minute,
+    FiveMinutes,
+    Hour,
+    Day,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] = [
+        CandleInterval::Minute,
+        CandleInterval::FiveMinutes,
+        CandleInterval::Hour,
+        CandleInterval::Day,
+    ];
+
+    /// Length of the interval in microseconds.
+    pub fn micros(self) -> u64 {
+        match self {
+            CandleInterval::Minute => 60_000_000,
+            CandleInterval::FiveMinutes => 300_000_000,
+            CandleInterval::Hour => 3_600_000_000,
+            CandleInterval::Day => 86_400_000_000,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`, in microseconds.
+    pub fn bucket_start(self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.micros()
+    }
+}
+
+/// Identifies the candles of one token at one interval.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CandleSeries {
+    pub token: String,
+    pub interval: CandleInterval,
+}
+
+/// Identifies the event counts of one event type at one interval.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RateSeries {
+    pub event_type: DashboardEventType,
+    pub interval: CandleInterval,
+}
+
+/// Open, high, low and close prices of a token over one interval. Prices are converted to
+/// floating point for charting.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Candle {
+    /// Start of the interval, in microseconds.
+    pub start: u64,
+    #[serde(with = "crate::float_bits")]
+    pub open: f64,
+    #[serde(with = "crate::float_bits")]
+    pub high: f64,
+    #[serde(with = "crate::float_bits")]
+    pub low: f64,
+    #[serde(with = "crate::float_bits")]
+    pub close: f64,
+    /// Number of price updates in the interval.
+    pub updates: u64,
+    /// When the update setting `open` was committed, in microseconds.
+    pub opened_at: u64,
+    /// When the update setting `close` was committed, in microseconds.
+    pub closed_at: u64,
+}
+
+impl Candle {
+    /// A candle starting at `start` with a single update.
+    pub fn new(start: u64, price: f64, timestamp: u64) -> Self {
+        Candle {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            updates: 1,
+            opened_at: timestamp,
+            closed_at: timestamp,
+        }
+    }
+
+    /// Adds an update to the candle. Updates may arrive out of order.
+    pub fn record(&mut self, price: f64, timestamp: u64) {
+        if timestamp < self.opened_at {
+            self.open = price;
+            self.opened_at = timestamp;
+        }
+        if timestamp >= self.closed_at {
+            self.close = price;
+            self.closed_at = timestamp;
+        }
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.updates += 1;
+    }
+}
+
+/// Number of events received during one interval.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct EventBucket {
+    /// Start of the interval, in microseconds.
+    pub start: u64,
+    pub count: u64,
+}
+
+/// Number of buckets kept per candle or event-rate series. Older buckets are pruned when a new
+/// one starts.
+pub const MAX_BUCKETS_PER_SERIES: u64 = 1_000;
+
+/// A value of a derived series, such as a moving average, at the start of an interval.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct SeriesPoint {
+    /// Start of the interval, in microseconds.
+    pub start: u64,
+    pub value: f64,
+}
+
+/// Width of the score histogram's buckets.
+pub const SCORE_BUCKET_WIDTH: f64 = 10.0;
+
+/// Index of the histogram bucket containing `score`.
+pub fn score_bucket(score: Score) -> i64 {
+    (score / SCORE_BUCKET_WIDTH).floor() as i64
+}
+
+/// Number of users whose latest score lies in `[lower, upper)`.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct HistogramBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub users: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{score_bucket, CandleInterval, SCORE_BUCKET_WIDTH};
+
+    #[test]
+    fn bucket_start_rounds_down_to_the_interval() {
+        let minute = CandleInterval::Minute.micros();
+        assert_eq!(CandleInterval::Minute.bucket_start(0), 0);
+        assert_eq!(CandleInterval::Minute.bucket_start(minute - 1), 0);
+        assert_eq!(CandleInterval::Minute.bucket_start(minute), minute);
+        assert_eq!(
+            CandleInterval::Minute.bucket_start(3 * minute + 17),
+            3 * minute
+        );
+    }
+
+    #[test]
+    fn bucket_start_is_aligned_for_every_interval() {
+        let timestamp = 1_700_000_123_456_789;
+        for interval in CandleInterval::ALL {
+            let start = interval.bucket_start(timestamp);
+            assert_eq!(start % interval.micros(), 0);
+            assert!(start <= timestamp && timestamp - start < interval.micros());
+        }
+    }
+
+    #[test]
+    fn score_bucket_includes_its_lower_bound() {
+        assert_eq!(score_bucket(0.0), 0);
+        assert_eq!(score_bucket(SCORE_BUCKET_WIDTH - 0.5), 0);
+        assert_eq!(score_bucket(SCORE_BUCKET_WIDTH), 1);
+        assert_eq!(score_bucket(99.9), 9);
+    }
+
+    #[test]
+    fn score_bucket_rounds_negative_scores_down() {
+        assert_eq!(score_bucket(-0.5), -1);
+        assert_eq!(score_bucket(-SCORE_BUCKET_WIDTH), -1);
+        assert_eq!(score_bucket(-SCORE_BUCKET_WIDTH - 0.5), -2);
+    }
+}
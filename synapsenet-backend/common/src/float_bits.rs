@@ -0,0 +1,91 @@
+//! Serde helpers for floats in types that Linera writes with BCS, which has no floating-point
+//! types. Binary formats store a float's bits as a `u64`; human-readable formats such as JSON
+//! still write a number. Use with `#[serde(with = "float_bits")]`, or `float_bits::option` for
+//! optional floats.
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_f64(*value)
+    } else {
+        serializer.serialize_u64(value.to_bits())
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    if deserializer.is_human_readable() {
+        f64::deserialize(deserializer)
+    } else {
+        u64::deserialize(deserializer).map(f64::from_bits)
+    }
+}
+
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else {
+            match value {
+                Some(value) => serializer.serialize_some(&value.to_bits()),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<f64>, D::Error> {
+        if deserializer.is_human_readable() {
+            Option::<f64>::deserialize(deserializer)
+        } else {
+            Ok(Option::<u64>::deserialize(deserializer)?.map(f64::from_bits))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Sample {
+        #[serde(with = "super")]
+        value: f64,
+        #[serde(with = "super::option")]
+        previous: Option<f64>,
+    }
+
+    #[test]
+    fn round_trips_through_bcs() {
+        for sample in [
+            Sample {
+                value: -12.5,
+                previous: Some(0.1),
+            },
+            Sample {
+                value: 0.0,
+                previous: None,
+            },
+        ] {
+            let bytes = linera_sdk::bcs::to_bytes(&sample).unwrap();
+            assert_eq!(
+                linera_sdk::bcs::from_bytes::<Sample>(&bytes).unwrap(),
+                sample
+            );
+        }
+    }
+
+    #[test]
+    fn stays_numeric_in_json() {
+        let sample = Sample {
+            value: 1.5,
+            previous: None,
+        };
+        let json = serde_json::to_string(&sample).unwrap();
+        assert_eq!(json, r#"{"value":1.5,"previous":null}"#);
+        assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), sample);
+    }
+}
@@ -0,0 +1,13 @@
+use async_graphql::Value;
+
+/// Formats a mutation calling `field` with `arguments`. Each argument is written in GraphQL
+/// input syntax, as produced by `InputType::to_value`, so that it parses back into the same
+/// value in the application's mutation root.
+pub fn mutation(field: &str, arguments: &[(&str, Value)]) -> String {
+    let arguments = arguments
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("mutation {{ {field}({arguments}) }}")
+}
@@ -0,0 +1,655 @@
+//! ABI and types of the identity-score application.
+
+use std::collections::BTreeSet;
+
+use async_graphql::{Enum, InputType, Request, Response, SimpleObject};
+use linera_sdk::linera_base_types::{
+    AccountOwner, ApplicationId, ChainId, ContractAbi, ServiceAbi,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{graphql::mutation, Score, UserId};
+
+pub struct IdentityScoreAbi;
+
+impl ContractAbi for IdentityScoreAbi {
+    type Operation = Operation;
+    type Response = Result<IdentityScoreResponse, IdentityScoreError>;
+}
+
+impl ServiceAbi for IdentityScoreAbi {
+    type Query = Request;
+    type QueryResponse = Response;
+}
+
+/// The identity-score service exposes every operation except `ReadEvent` and `GetScore` as a
+/// mutation.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Operation {
+    /// Sets the score of `user_id`. Their signals are adjusted to produce it, so later signals
+    /// build on the new score. Admin only.
+    UpdateScore {
+        user_id: UserId,
+        #[serde(with = "crate::float_bits")]
+        score: Score,
+        reason: String,
+    },
+    /// Claims `user_id` for the signer. Each user id and each owner can only be bound once.
+    Register { user_id: UserId },
+    /// Records a signal about a user and recomputes their score with the application's
+    /// [`ScoreModel`]. Self-reported kinds must be signed by the owner of `user_id`, all other
+    /// kinds by a signal issuer.
+    RecordSignal {
+        user_id: UserId,
+        kind: SignalKind,
+        #[serde(with = "crate::float_bits")]
+        value: f64,
+        reason: String,
+    },
+    /// Attests `claim` about `subject` on behalf of the signer, replacing the signer's previous
+    /// attestation of the same claim. `expiry` is in microseconds.
+    Attest {
+        subject: String,
+        claim: String,
+        expiry: Option<u64>,
+    },
+    /// Revokes the signer's attestation of `claim` about `subject`.
+    Revoke { subject: String, claim: String },
+    /// Allows `owner` to record signals that are not self-reported. Admin only.
+    AddSignalIssuer { owner: AccountOwner },
+    /// Revokes `owner`'s right to record signals that are not self-reported. Admin only.
+    RemoveSignalIssuer { owner: AccountOwner },
+    /// Returns the event at `index` of the score stream emitted on `chain_id`. Meant for
+    /// applications subscribed to the stream, which cannot read another application's events
+    /// themselves.
+    ReadEvent { chain_id: ChainId, index: u32 },
+    /// Returns the [`ScoreStatus`] of `user_id` without changing any state. Meant for other
+    /// applications gating actions on scores through `call_application`.
+    GetScore { user_id: UserId },
+}
+
+impl Operation {
+    /// The GraphQL mutation scheduling this operation through the application's service, or
+    /// `None` for `ReadEvent` and `GetScore`, which are only available through
+    /// `call_application`.
+    pub fn to_mutation(&self) -> Option<String> {
+        let mutation = match self {
+            Operation::UpdateScore {
+                user_id,
+                score,
+                reason,
+            } => mutation(
+                "updateScore",
+                &[
+                    ("userId", user_id.to_value()),
+                    ("score", score.to_value()),
+                    ("reason", reason.to_value()),
+                ],
+            ),
+            Operation::Register { user_id } => {
+                mutation("register", &[("userId", user_id.to_value())])
+            }
+            Operation::RecordSignal {
+                user_id,
+                kind,
+                value,
+                reason,
+            } => mutation(
+                "recordSignal",
+                &[
+                    ("userId", user_id.to_value()),
+                    ("kind", kind.to_value()),
+                    ("value", value.to_value()),
+                    ("reason", reason.to_value()),
+                ],
+            ),
+            Operation::Attest {
+                subject,
+                claim,
+                expiry,
+            } => mutation(
+                "attest",
+                &[
+                    ("subject", subject.to_value()),
+                    ("claim", claim.to_value()),
+                    ("expiry", expiry.to_value()),
+                ],
+            ),
+            Operation::Revoke { subject, claim } => mutation(
+                "revoke",
+                &[("subject", subject.to_value()), ("claim", claim.to_value())],
+            ),
+            Operation::AddSignalIssuer { owner } => {
+                mutation("addSignalIssuer", &[("owner", owner.to_value())])
+            }
+            Operation::RemoveSignalIssuer { owner } => {
+                mutation("removeSignalIssuer", &[("owner", owner.to_value())])
+            }
+            Operation::ReadEvent { .. } | Operation::GetScore { .. } => return None,
+        };
+        Some(mutation)
+    }
+}
+
+/// The successful outcome of an identity-score operation.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum IdentityScoreResponse {
+    Ok,
+    Status(ScoreStatus),
+    Event(IdentityScoreEvent),
+}
+
+/// A user's score and attestation status at the time of the call.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, SimpleObject)]
+pub struct ScoreStatus {
+    /// The decayed score, or `None` if the user was never scored.
+    #[serde(with = "crate::float_bits::option")]
+    pub score: Option<Score>,
+    /// The owner that claimed the user id, if any.
+    pub owner: Option<AccountOwner>,
+    /// Claims currently validly attested about the user.
+    pub attested_claims: Vec<String>,
+}
+
+/// Why an identity-score operation was rejected. Errors are returned instead of failing the
+/// block, so operations check their inputs before writing any state.
+#[derive(Clone, Debug, Deserialize, Serialize, Error, PartialEq)]
+pub enum IdentityScoreError {
+    #[error("user id must not be empty")]
+    EmptyUserId,
+    #[error("{field} must be a finite number")]
+    NonFinite { field: String },
+    #[error("score {score} is outside the allowed range [{min}, {max}]")]
+    OutOfRange {
+        #[serde(with = "crate::float_bits")]
+        score: f64,
+        #[serde(with = "crate::float_bits")]
+        min: f64,
+        #[serde(with = "crate::float_bits")]
+        max: f64,
+    },
+    #[error("operation must be signed")]
+    Unauthenticated,
+    #[error("attestation expiry {expiry} is not in the future")]
+    ExpiryInPast { expiry: u64 },
+    #[error("no attestation of {claim} about {subject} by the signer")]
+    AttestationNotFound { subject: String, claim: String },
+    #[error("user id {user_id} is already claimed")]
+    UserIdTaken { user_id: String },
+    #[error("the signer already owns user id {user_id}")]
+    OwnerAlreadyRegistered { user_id: String },
+    #[error("user id {user_id} is not owned by the signer")]
+    NotOwner { user_id: String },
+    #[error("{owner} is not authorized to perform this operation")]
+    Unauthorized { owner: AccountOwner },
+}
+
+/// The kinds of evidence a score is derived from.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize, Enum)]
+pub enum SignalKind {
+    OnChainActivity,
+    Attestation,
+    Penalty,
+}
+
+impl SignalKind {
+    /// Whether signals of this kind are reported by the user themselves, and so must be signed
+    /// by the owner of the user id.
+    pub fn is_self_reported(self) -> bool {
+        matches!(self, SignalKind::OnChainActivity)
+    }
+}
+
+/// How much a kind of signal counts towards a score.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct SignalWeight {
+    pub kind: SignalKind,
+    /// Multiplier applied to the accumulated value of this kind.
+    pub weight: f64,
+    /// Fraction of the accumulated value of this kind that is dropped whenever a new signal of
+    /// the kind arrives, between 0 (never forget) and 1 (only keep the latest signal).
+    pub decay: f64,
+}
+
+/// The accumulated value of one kind of signal for a user.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct SignalTotal {
+    pub kind: SignalKind,
+    #[serde(with = "crate::float_bits")]
+    pub total: f64,
+}
+
+/// Everything a user's score is derived from.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SimpleObject)]
+pub struct UserSignals {
+    pub totals: Vec<SignalTotal>,
+    /// Offset added by `UpdateScore` so that the signals produce the score it set, and by
+    /// clamping so that they never produce a score out of range. It decays like the totals.
+    #[serde(with = "crate::float_bits")]
+    pub adjustment: f64,
+}
+
+/// Checks that `user_id` can be scored.
+pub fn check_user_id(user_id: &str) -> Result<(), IdentityScoreError> {
+    if user_id.is_empty() {
+        return Err(IdentityScoreError::EmptyUserId);
+    }
+    Ok(())
+}
+
+/// Checks that `value`, given for `field`, is a usable number.
+pub fn check_finite(field: &str, value: f64) -> Result<(), IdentityScoreError> {
+    if !value.is_finite() {
+        return Err(IdentityScoreError::NonFinite {
+            field: field.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// The formula turning a user's signals into a score, fixed when the application is created:
+/// `baseline + adjustment + sum(weight * total)` over all signal kinds.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct ScoreModel {
+    /// Score of a user without any signals.
+    pub baseline: f64,
+    /// Kinds without a weight do not affect scores.
+    pub weights: Vec<SignalWeight>,
+    /// Time after which an inactive user's distance to `baseline` has halved. Scores never
+    /// decay if unset.
+    #[serde(default)]
+    pub half_life_secs: Option<u64>,
+    /// Lowest allowed score.
+    #[serde(default = "ScoreModel::default_min_score")]
+    pub min_score: f64,
+    /// Highest allowed score.
+    #[serde(default = "ScoreModel::default_max_score")]
+    pub max_score: f64,
+}
+
+impl ScoreModel {
+    fn default_min_score() -> f64 {
+        0.0
+    }
+
+    fn default_max_score() -> f64 {
+        100.0
+    }
+
+    /// Checks that the model only contains finite numbers, so that the scores it derives are.
+    pub fn check(&self) -> Result<(), IdentityScoreError> {
+        check_finite("baseline", self.baseline)?;
+        check_finite("min_score", self.min_score)?;
+        check_finite("max_score", self.max_score)?;
+        for weight in &self.weights {
+            check_finite("weight", weight.weight)?;
+            check_finite("decay", weight.decay)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that `score` is finite and within `[min_score, max_score]`.
+    pub fn check_score(&self, score: f64) -> Result<(), IdentityScoreError> {
+        check_finite("score", score)?;
+        if score < self.min_score || score > self.max_score {
+            return Err(IdentityScoreError::OutOfRange {
+                score,
+                min: self.min_score,
+                max: self.max_score,
+            });
+        }
+        Ok(())
+    }
+
+    /// Factor by which the distance to `baseline` shrinks after `elapsed` microseconds.
+    pub fn decay_factor(&self, elapsed: u64) -> f64 {
+        match self.half_life_secs {
+            Some(half_life) if half_life > 0 => {
+                0.5_f64.powf(elapsed as f64 / (half_life as f64 * 1_000_000.0))
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// The score of `record` once decayed up to `now` (microseconds).
+    pub fn decayed(&self, record: &ScoreRecord, now: u64) -> f64 {
+        let factor = self.decay_factor(now.saturating_sub(record.last_activity));
+        self.baseline + (record.score - self.baseline) * factor
+    }
+
+    /// Applies `elapsed` microseconds of decay to `signals`, which decays the score they produce.
+    pub fn decay_signals(&self, signals: &mut UserSignals, elapsed: u64) {
+        let factor = self.decay_factor(elapsed);
+        for total in &mut signals.totals {
+            total.total *= factor;
+        }
+        signals.adjustment *= factor;
+    }
+
+    fn weight(&self, kind: SignalKind) -> Option<&SignalWeight> {
+        self.weights.iter().find(|weight| weight.kind == kind)
+    }
+
+    /// Adds a signal of `kind` to `signals`, decaying the previous total of that kind.
+    pub fn accumulate(&self, signals: &mut UserSignals, kind: SignalKind, value: f64) {
+        let decay = self
+            .weight(kind)
+            .map_or(0.0, |weight| weight.decay.clamp(0.0, 1.0));
+        match signals.totals.iter_mut().find(|total| total.kind == kind) {
+            Some(total) => total.total = total.total * (1.0 - decay) + value,
+            None => signals.totals.push(SignalTotal { kind, total: value }),
+        }
+    }
+
+    /// Checks that `signals` produce a finite score before clamping. Large finite signals can
+    /// overflow the totals, and clamping would turn the resulting NaN into `max_score`.
+    pub fn check_signals(&self, signals: &UserSignals) -> Result<(), IdentityScoreError> {
+        for total in &signals.totals {
+            check_finite("signal total", total.total)?;
+        }
+        check_finite(
+            "score",
+            self.unclamped_score(&signals.totals) + signals.adjustment,
+        )
+    }
+
+    /// Adjusts `signals` so that they produce `score`, which must be within
+    /// `[min_score, max_score]`.
+    pub fn override_score(&self, signals: &mut UserSignals, score: f64) {
+        signals.adjustment = score - self.unclamped_score(&signals.totals);
+    }
+
+    fn unclamped_score(&self, totals: &[SignalTotal]) -> f64 {
+        totals.iter().fold(self.baseline, |score, total| {
+            score + self.weight(total.kind).map_or(0.0, |weight| weight.weight) * total.total
+        })
+    }
+
+    /// The score resulting from `signals`, limited to `[min_score, max_score]`.
+    pub fn score(&self, signals: &UserSignals) -> f64 {
+        let score = self.unclamped_score(&signals.totals) + signals.adjustment;
+        score.min(self.max_score).max(self.min_score)
+    }
+
+    /// Returns the score resulting from `signals`, folding any clamping into their adjustment.
+    /// Decaying the signals then decays their score exactly like [`ScoreModel::decayed`]
+    /// decays the stored score.
+    pub fn settle_score(&self, signals: &mut UserSignals) -> f64 {
+        let score = self.score(signals);
+        self.override_score(signals, score);
+        score
+    }
+}
+
+/// Settings fixed when the identity-score application is created.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct IdentityScoreParameters {
+    /// The score model's fields are given at the top level of the parameters.
+    #[serde(flatten)]
+    pub model: ScoreModel,
+    /// Dashboard application that score changes are forwarded to, if any.
+    #[serde(default)]
+    pub dashboard: Option<ApplicationId>,
+}
+
+/// Argument used when creating the identity-score application.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct IdentityScoreInstantiation {
+    /// Owner allowed to set scores and manage signal issuers. Defaults to the signer creating
+    /// the application.
+    pub admin: Option<AccountOwner>,
+    /// Owners allowed to record signals that are not self-reported from the start.
+    pub signal_issuers: BTreeSet<AccountOwner>,
+}
+
+/// Name of the event stream on which score changes are emitted as [`IdentityScoreEvent`]s.
+pub const SCORE_STREAM_NAME: &[u8] = b"scores";
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum IdentityScoreEvent {
+    ScoreUpdate {
+        user_id: UserId,
+        /// The user's decayed score before the update, or `None` for a user's first score.
+        #[serde(with = "crate::float_bits::option")]
+        old_score: Option<Score>,
+        #[serde(with = "crate::float_bits")]
+        new_score: Score,
+        reason: String,
+        timestamp: u64,
+    },
+}
+
+/// A user's score as last written, before any decay.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct ScoreRecord {
+    #[serde(with = "crate::float_bits")]
+    pub score: f64,
+    /// When the score was last written, in microseconds.
+    pub last_activity: u64,
+}
+
+/// A user's current score.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct UserScore {
+    pub user_id: UserId,
+    /// The score decayed up to the time of the query.
+    pub score: Score,
+    /// The score as last written.
+    pub raw_score: Score,
+    pub last_activity: u64,
+}
+
+/// One entry of a user's append-only score history.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct ScoreChange {
+    /// Position of this entry in the user's history, starting at zero.
+    pub sequence: u64,
+    #[serde(with = "crate::float_bits")]
+    pub score: Score,
+    /// Difference from the previous decayed score, or the score itself for a user's first
+    /// entry.
+    #[serde(with = "crate::float_bits")]
+    pub delta: f64,
+    pub reason: String,
+    pub timestamp: u64,
+    /// The owner that signed the update, if any.
+    pub signer: Option<AccountOwner>,
+}
+
+/// A claim about a subject made by an issuer such as a KYC provider, a DAO or another
+/// application.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Attestation {
+    pub issuer: AccountOwner,
+    pub claim: String,
+    pub issued_at: u64,
+    /// When the attestation stops being valid, in microseconds.
+    pub expiry: Option<u64>,
+    /// When the issuer revoked the attestation, in microseconds.
+    pub revoked_at: Option<u64>,
+}
+
+impl Attestation {
+    /// Whether the attestation is neither revoked nor expired at `now` (microseconds).
+    pub fn is_valid(&self, now: u64) -> bool {
+        self.revoked_at.is_none() && self.expiry.is_none_or(|expiry| now < expiry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        IdentityScoreError, ScoreModel, ScoreRecord, SignalKind, SignalWeight, UserSignals,
+    };
+
+    fn model() -> ScoreModel {
+        ScoreModel {
+            baseline: 50.0,
+            weights: vec![
+                SignalWeight {
+                    kind: SignalKind::OnChainActivity,
+                    weight: 2.0,
+                    decay: 0.5,
+                },
+                SignalWeight {
+                    kind: SignalKind::Penalty,
+                    weight: -10.0,
+                    decay: 0.0,
+                },
+            ],
+            half_life_secs: None,
+            min_score: 0.0,
+            max_score: 100.0,
+        }
+    }
+
+    #[test]
+    fn signals_are_weighted_and_clamped() {
+        let model = model();
+        let mut signals = UserSignals::default();
+        assert_eq!(model.score(&signals), 50.0);
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, 5.0);
+        assert_eq!(model.score(&signals), 60.0);
+        model.accumulate(&mut signals, SignalKind::Attestation, 100.0);
+        assert_eq!(model.score(&signals), 60.0);
+        model.accumulate(&mut signals, SignalKind::Penalty, 10.0);
+        assert_eq!(model.score(&signals), 0.0);
+    }
+
+    #[test]
+    fn signals_overflowing_the_score_are_rejected() {
+        let model = model();
+        let mut signals = UserSignals::default();
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, f64::MAX);
+        assert_eq!(
+            model.check_signals(&signals),
+            Err(IdentityScoreError::NonFinite {
+                field: "score".to_string()
+            })
+        );
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, f64::MAX);
+        assert_eq!(
+            model.check_signals(&signals),
+            Err(IdentityScoreError::NonFinite {
+                field: "signal total".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn models_with_non_finite_numbers_are_rejected() {
+        assert_eq!(model().check(), Ok(()));
+        let mut weights = model().weights;
+        weights[0].decay = f64::NAN;
+        let model = ScoreModel { weights, ..model() };
+        assert_eq!(
+            model.check(),
+            Err(IdentityScoreError::NonFinite {
+                field: "decay".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn accumulating_decays_previous_total_of_kind() {
+        let model = model();
+        let mut signals = UserSignals::default();
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, 4.0);
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, 4.0);
+        assert_eq!(signals.totals[0].total, 6.0);
+    }
+
+    #[test]
+    fn overridden_score_is_kept_by_later_signals() {
+        let model = model();
+        let mut signals = UserSignals::default();
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, 5.0);
+        model.override_score(&mut signals, 80.0);
+        assert_eq!(model.score(&signals), 80.0);
+        model.accumulate(&mut signals, SignalKind::Penalty, 1.0);
+        assert_eq!(model.score(&signals), 70.0);
+    }
+
+    const HOUR: u64 = 3_600_000_000;
+
+    #[test]
+    fn score_halves_distance_to_baseline_every_half_life() {
+        let model = ScoreModel {
+            half_life_secs: Some(3_600),
+            ..model()
+        };
+        let record = ScoreRecord {
+            score: 90.0,
+            last_activity: HOUR,
+        };
+        assert_eq!(model.decayed(&record, HOUR), 90.0);
+        assert_eq!(model.decayed(&record, 2 * HOUR), 70.0);
+        assert_eq!(model.decayed(&record, 3 * HOUR), 60.0);
+        let record = ScoreRecord {
+            score: 10.0,
+            last_activity: HOUR,
+        };
+        assert_eq!(model.decayed(&record, 2 * HOUR), 30.0);
+    }
+
+    #[test]
+    fn score_does_not_decay_without_half_life() {
+        let record = ScoreRecord {
+            score: 90.0,
+            last_activity: 0,
+        };
+        for half_life_secs in [None, Some(0)] {
+            let model = ScoreModel {
+                half_life_secs,
+                ..model()
+            };
+            assert_eq!(model.decay_factor(u64::MAX), 1.0);
+            assert_eq!(model.decayed(&record, u64::MAX), 90.0);
+        }
+    }
+
+    #[test]
+    fn score_does_not_decay_before_last_activity() {
+        let model = ScoreModel {
+            half_life_secs: Some(3_600),
+            ..model()
+        };
+        let record = ScoreRecord {
+            score: 90.0,
+            last_activity: 2 * HOUR,
+        };
+        assert_eq!(model.decayed(&record, HOUR), 90.0);
+    }
+
+    #[test]
+    fn decayed_signals_match_decayed_score() {
+        let model = ScoreModel {
+            half_life_secs: Some(3_600),
+            ..model()
+        };
+        let mut signals = UserSignals::default();
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, 10.0);
+        model.override_score(&mut signals, 90.0);
+        let record = ScoreRecord {
+            score: model.score(&signals),
+            last_activity: 0,
+        };
+        model.decay_signals(&mut signals, HOUR);
+        assert_eq!(model.score(&signals), model.decayed(&record, HOUR));
+
+        // Signals beyond the range are clamped: 50 + 2 * 75 produces 100, not 200.
+        let mut signals = UserSignals::default();
+        model.accumulate(&mut signals, SignalKind::OnChainActivity, 75.0);
+        let record = ScoreRecord {
+            score: model.settle_score(&mut signals),
+            last_activity: 0,
+        };
+        assert_eq!(record.score, 100.0);
+        model.decay_signals(&mut signals, HOUR);
+        assert_eq!(model.score(&signals), model.decayed(&record, HOUR));
+        // A signal without value leaves the decayed score as it is.
+        model.accumulate(&mut signals, SignalKind::Penalty, 0.0);
+        assert_eq!(model.settle_score(&mut signals), 75.0);
+    }
+}
@@ -0,0 +1,12 @@
+//! Types shared by the SynapseNet applications and the SDK: the applications' ABIs,
+//! operations, messages and events, and the GraphQL types they expose. Keeping them in one
+//! crate ensures that both ends of every wire format agree.
+
+pub mod dashboard;
+pub mod float_bits;
+pub mod graphql;
+pub mod identity_score;
+pub mod price_feed;
+mod types;
+
+pub use types::{AppInfo, Price, Score, UserId};
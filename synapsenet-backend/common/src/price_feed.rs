@@ -0,0 +1,385 @@
+//! ABI and types of the price-feed application.
+
+use std::{collections::BTreeSet, fmt};
+
+use async_graphql::{
+    InputObject, InputType, InputValueError, InputValueResult, Request, Response, Scalar,
+    SimpleObject, Value,
+};
+use linera_sdk::linera_base_types::{
+    AccountOwner, ApplicationId, ChainId, ContractAbi, ServiceAbi,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{graphql::mutation, Price};
+
+pub struct PriceFeedAbi;
+
+impl ContractAbi for PriceFeedAbi {
+    type Operation = Operation;
+    type Response = PriceFeedResponse;
+}
+
+impl ServiceAbi for PriceFeedAbi {
+    type Query = Request;
+    type QueryResponse = Response;
+}
+
+/// The price-feed service exposes every operation except `ReadEvent` as a mutation.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Operation {
+    UpdatePrice {
+        token: String,
+        price: Price,
+    },
+    /// Submits the signer's answer for an oracle round. The round's price is committed once a
+    /// quorum of publishers agree; submissions for rounds that are not newer than the last
+    /// committed round of `token` are rejected.
+    UpdateRound {
+        token: String,
+        price: Price,
+        round: RoundMetadata,
+    },
+    /// Allows `owner` to publish prices. Admin only.
+    AddPublisher {
+        owner: AccountOwner,
+    },
+    /// Revokes `owner`'s right to publish prices. Admin only.
+    RemovePublisher {
+        owner: AccountOwner,
+    },
+    /// Sets or clears the thresholds deciding which prices of `token` are committed. Admin only.
+    SetUpdateThresholds {
+        token: String,
+        thresholds: Option<UpdateThresholds>,
+    },
+    /// Returns the event at `index` of the price stream emitted on `chain_id`. Meant for
+    /// applications subscribed to the stream, which cannot read another application's events
+    /// themselves.
+    ReadEvent {
+        chain_id: ChainId,
+        index: u32,
+    },
+}
+
+impl Operation {
+    /// The GraphQL mutation scheduling this operation through the application's service, or
+    /// `None` for `ReadEvent`, which is only available through `call_application`.
+    pub fn to_mutation(&self) -> Option<String> {
+        let mutation = match self {
+            Operation::UpdatePrice { token, price } => mutation(
+                "updatePrice",
+                &[("token", token.to_value()), ("price", price.to_value())],
+            ),
+            Operation::UpdateRound {
+                token,
+                price,
+                round,
+            } => mutation(
+                "updateRound",
+                &[
+                    ("token", token.to_value()),
+                    ("price", price.to_value()),
+                    ("round", round.to_value()),
+                ],
+            ),
+            Operation::AddPublisher { owner } => {
+                mutation("addPublisher", &[("owner", owner.to_value())])
+            }
+            Operation::RemovePublisher { owner } => {
+                mutation("removePublisher", &[("owner", owner.to_value())])
+            }
+            Operation::SetUpdateThresholds { token, thresholds } => mutation(
+                "setUpdateThresholds",
+                &[
+                    ("token", token.to_value()),
+                    ("thresholds", thresholds.to_value()),
+                ],
+            ),
+            Operation::ReadEvent { .. } => return None,
+        };
+        Some(mutation)
+    }
+}
+
+/// The outcome of a price-feed operation.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub enum PriceFeedResponse {
+    #[default]
+    Ok,
+    Event(PriceFeedEvent),
+}
+
+/// Aggregation settings fixed when the price-feed application is created.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct PriceFeedParameters {
+    /// Number of agreeing publisher submissions needed before a round's price is committed.
+    pub quorum: u32,
+    /// Submissions further than this from the round's median, in basis points, are discarded
+    /// as outliers.
+    pub max_deviation_bps: u32,
+    /// Dashboard application that committed prices are forwarded to, if any.
+    #[serde(default)]
+    pub dashboard: Option<ApplicationId>,
+}
+
+impl Default for PriceFeedParameters {
+    fn default() -> Self {
+        PriceFeedParameters {
+            quorum: 1,
+            max_deviation_bps: 500,
+            dashboard: None,
+        }
+    }
+}
+
+impl PriceFeedParameters {
+    /// Returns the canonical price of a round once at least `quorum` of its submissions lie
+    /// within `max_deviation_bps` of the median: the median of those submissions.
+    pub fn aggregate(&self, prices: &[Price]) -> Option<Price> {
+        let quorum = self.quorum.max(1) as usize;
+        if prices.len() < quorum {
+            return None;
+        }
+        let median = Price::median(prices)?;
+        let inliers = prices
+            .iter()
+            .copied()
+            .filter(|price| {
+                price
+                    .deviation_bps(median)
+                    .is_some_and(|bps| bps <= u128::from(self.max_deviation_bps))
+            })
+            .collect::<Vec<_>>();
+        if inliers.len() < quorum {
+            return None;
+        }
+        Price::median(&inliers)
+    }
+}
+
+/// Per-token rules for committing prices: a price that neither moved by more than
+/// `deviation_bps` from the latest committed price nor arrived `heartbeat_secs` after it is
+/// coalesced into the latest price instead of being stored.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, SimpleObject, InputObject)]
+#[graphql(input_name = "UpdateThresholdsInput")]
+pub struct UpdateThresholds {
+    pub deviation_bps: u32,
+    pub heartbeat_secs: u64,
+}
+
+impl UpdateThresholds {
+    /// Whether `price`, observed at `now` (microseconds), should be committed after `latest`.
+    pub fn is_due(&self, latest: &PricePoint, price: Price, now: u64) -> bool {
+        let heartbeat = self.heartbeat_secs.saturating_mul(1_000_000);
+        let heartbeat_passed = now.saturating_sub(latest.timestamp) >= heartbeat;
+        let deviated = price
+            .deviation_bps(latest.price)
+            .is_none_or(|bps| bps > u128::from(self.deviation_bps));
+        heartbeat_passed || deviated
+    }
+}
+
+/// Argument used when creating the price-feed application.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PriceFeedInstantiation {
+    /// Owner allowed to add and remove publishers. Defaults to the signer creating the
+    /// application.
+    pub admin: Option<AccountOwner>,
+    /// Owners allowed to publish prices from the start.
+    pub publishers: BTreeSet<AccountOwner>,
+}
+
+/// Name of the event stream on which committed prices are emitted as [`PriceFeedEvent`]s.
+pub const PRICE_STREAM_NAME: &[u8] = b"prices";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum PriceFeedEvent {
+    PriceUpdate {
+        token: String,
+        price: Price,
+        round: Option<RoundMetadata>,
+        timestamp: u64,
+    },
+}
+
+/// A single recorded price for a token, timestamped in microseconds.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct PricePoint {
+    pub price: Price,
+    /// The oracle round the price was read from, if it was published with `UpdateRound`.
+    pub round: Option<RoundMetadata>,
+    pub timestamp: u64,
+}
+
+/// A publisher's price for an oracle round, awaiting or part of the round's aggregation.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct Submission {
+    pub publisher: AccountOwner,
+    pub price: Price,
+    pub timestamp: u64,
+}
+
+/// All submissions received for one round of a token.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct RoundSubmissions {
+    pub round_id: RoundId,
+    pub submissions: Vec<Submission>,
+    /// The aggregated price, once the round reached quorum.
+    pub committed: Option<Price>,
+}
+
+/// Chainlink `latestRoundData` metadata accompanying a price.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject, InputObject)]
+#[graphql(input_name = "RoundMetadataInput")]
+pub struct RoundMetadata {
+    pub round_id: RoundId,
+    pub answered_in_round: RoundId,
+    /// When the round started, in oracle seconds.
+    pub started_at: u64,
+    /// When the round's answer was last updated, in oracle seconds.
+    pub updated_at: u64,
+    /// Name of the network the oracle was read from, e.g. `Polygon Amoy`.
+    pub network: String,
+}
+
+/// A Chainlink round id. Round ids are `uint80` on-chain, so they are carried as `u128` and
+/// written as strings in GraphQL.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct RoundId(pub u128);
+
+impl fmt::Display for RoundId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[Scalar]
+impl async_graphql::ScalarType for RoundId {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match &value {
+            Value::String(raw) => raw.parse().map(RoundId).map_err(InputValueError::custom),
+            Value::Number(raw) => raw
+                .as_u64()
+                .map(|raw| RoundId(raw.into()))
+                .ok_or_else(|| InputValueError::custom("round id must be a non-negative integer")),
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.0.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Price, PriceFeedParameters, PricePoint, UpdateThresholds};
+
+    fn parameters(quorum: u32, max_deviation_bps: u32) -> PriceFeedParameters {
+        PriceFeedParameters {
+            quorum,
+            max_deviation_bps,
+            dashboard: None,
+        }
+    }
+
+    fn prices(values: &[i128]) -> Vec<Price> {
+        values.iter().map(|value| Price::new(*value, 2)).collect()
+    }
+
+    #[test]
+    fn aggregate_waits_for_quorum() {
+        let parameters = parameters(3, 500);
+        assert_eq!(parameters.aggregate(&prices(&[10_000, 10_010])), None);
+        assert_eq!(
+            parameters.aggregate(&prices(&[10_000, 10_010, 10_020])),
+            Some(Price::new(10_010, 2))
+        );
+    }
+
+    #[test]
+    fn aggregate_discards_outliers() {
+        let parameters = parameters(3, 100);
+        let round = prices(&[10_000, 10_020, 10_040, 20_000]);
+        assert_eq!(parameters.aggregate(&round), Some(Price::new(10_020, 2)));
+    }
+
+    #[test]
+    fn aggregate_needs_quorum_of_inliers() {
+        let parameters = parameters(3, 100);
+        let round = prices(&[10_000, 10_020, 15_000, 20_000]);
+        assert_eq!(parameters.aggregate(&round), None);
+    }
+
+    #[test]
+    fn aggregate_treats_zero_quorum_as_one() {
+        let parameters = parameters(0, 0);
+        assert_eq!(parameters.aggregate(&[]), None);
+        assert_eq!(
+            parameters.aggregate(&prices(&[10_000])),
+            Some(Price::new(10_000, 2))
+        );
+    }
+
+    fn latest(value: i128, timestamp: u64) -> PricePoint {
+        PricePoint {
+            price: Price::new(value, 2),
+            round: None,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn price_is_due_after_deviation() {
+        let thresholds = UpdateThresholds {
+            deviation_bps: 100,
+            heartbeat_secs: 3_600,
+        };
+        let latest = latest(10_000, 0);
+        assert!(!thresholds.is_due(&latest, Price::new(10_100, 2), 1));
+        assert!(thresholds.is_due(&latest, Price::new(10_101, 2), 1));
+        assert!(thresholds.is_due(&latest, Price::new(9_899, 2), 1));
+    }
+
+    #[test]
+    fn price_is_due_after_heartbeat() {
+        let thresholds = UpdateThresholds {
+            deviation_bps: 100,
+            heartbeat_secs: 60,
+        };
+        let latest = latest(10_000, 1_000_000);
+        assert!(!thresholds.is_due(&latest, Price::new(10_000, 2), 60_999_999));
+        assert!(thresholds.is_due(&latest, Price::new(10_000, 2), 61_000_000));
+    }
+
+    #[test]
+    fn price_is_not_due_before_latest() {
+        let thresholds = UpdateThresholds {
+            deviation_bps: 100,
+            heartbeat_secs: 60,
+        };
+        let latest = latest(10_000, 1_000_000);
+        assert!(!thresholds.is_due(&latest, Price::new(10_000, 2), 0));
+    }
+
+    #[test]
+    fn huge_heartbeat_does_not_overflow() {
+        let thresholds = UpdateThresholds {
+            deviation_bps: 100,
+            heartbeat_secs: u64::MAX,
+        };
+        let latest = latest(10_000, 0);
+        assert!(!thresholds.is_due(&latest, Price::new(10_000, 2), u64::MAX - 1));
+        assert!(thresholds.is_due(&latest, Price::new(10_000, 2), u64::MAX));
+    }
+
+    #[test]
+    fn price_after_zero_price_is_always_due() {
+        let thresholds = UpdateThresholds {
+            deviation_bps: u32::MAX,
+            heartbeat_secs: u64::MAX,
+        };
+        assert!(thresholds.is_due(&latest(0, 0), Price::new(0, 2), 1));
+    }
+}
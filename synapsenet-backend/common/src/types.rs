@@ -0,0 +1,189 @@
+use async_graphql::{
+    InputValueError, InputValueResult, Name, Scalar, ScalarType, SimpleObject, Value,
+};
+use linera_sdk::linera_base_types::{ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+
+/// A user id scored by the identity-score application.
+pub type UserId = String;
+
+/// An identity score, within the bounds of the application's score model.
+pub type Score = f64;
+
+/// Where and when an instance of an application was created, as reported by the runtime.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject)]
+pub struct AppInfo {
+    /// Chain this instance runs on.
+    pub chain_id: ChainId,
+    pub application_id: ApplicationId,
+    /// Chain on which the application was created.
+    pub creator_chain_id: ChainId,
+    /// When the application was instantiated, in microseconds.
+    pub created_at: u64,
+}
+
+/// A fixed-point price: `value` scaled by `10^decimals`, exactly as oracles such as Chainlink
+/// report their answers.
+///
+/// In GraphQL it is written as `{ value: "301245000000", decimals: 8 }`, with `value` given as a
+/// string so that it is not truncated by clients that only handle 53-bit integers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Price {
+    pub value: i128,
+    pub decimals: u8,
+}
+
+impl Price {
+    pub fn new(value: i128, decimals: u8) -> Self {
+        Price { value, decimals }
+    }
+
+    /// Returns the same price with `decimals` places, unless that would lose precision or
+    /// overflow.
+    pub fn rescaled(self, decimals: u8) -> Option<Price> {
+        let extra = decimals.checked_sub(self.decimals)?;
+        let factor = 10i128.checked_pow(u32::from(extra))?;
+        Some(Price::new(self.value.checked_mul(factor)?, decimals))
+    }
+
+    /// Distance from `reference` in basis points of `reference`, or `None` if the two cannot be
+    /// compared.
+    pub fn deviation_bps(self, reference: Price) -> Option<u128> {
+        let decimals = self.decimals.max(reference.decimals);
+        let price = self.rescaled(decimals)?.value;
+        let reference = reference.rescaled(decimals)?.value;
+        if reference == 0 {
+            return None;
+        }
+        let distance = price.checked_sub(reference)?.unsigned_abs();
+        Some(distance.checked_mul(10_000)? / reference.unsigned_abs())
+    }
+
+    /// Parses a decimal string such as `"3012.45"` into a price with `decimals` places, unless
+    /// it is malformed or has more decimal places than that.
+    pub fn from_decimal_str(text: &str, decimals: u8) -> Option<Price> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() || fraction.len() > decimals as usize {
+            return None;
+        }
+        let padded = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+        if !padded.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let value = padded.parse::<i128>().ok()?;
+        Some(Price::new(if negative { -value } else { value }, decimals))
+    }
+
+    /// The price as a floating-point number, for display and charting only.
+    pub fn to_f64(self) -> f64 {
+        self.value as f64 / 10f64.powi(i32::from(self.decimals))
+    }
+
+    /// The median of `prices`, with as many decimals as the most precise of them. For an even
+    /// number of prices, the two middle ones are averaged, rounding toward zero.
+    pub fn median(prices: &[Price]) -> Option<Price> {
+        let decimals = prices.iter().map(|price| price.decimals).max()?;
+        let mut values = prices
+            .iter()
+            .map(|price| Some(price.rescaled(decimals)?.value))
+            .collect::<Option<Vec<_>>>()?;
+        values.sort_unstable();
+        let middle = values.len() / 2;
+        let value = if values.len() % 2 == 1 {
+            values[middle]
+        } else {
+            values[middle - 1].checked_add(values[middle])? / 2
+        };
+        Some(Price::new(value, decimals))
+    }
+}
+
+#[Scalar]
+impl ScalarType for Price {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        let Value::Object(fields) = &value else {
+            return Err(InputValueError::expected_type(value));
+        };
+        let raw = match fields.get("value") {
+            Some(Value::String(raw)) => raw.parse::<i128>().ok(),
+            Some(Value::Number(raw)) => raw.as_i64().map(i128::from),
+            _ => None,
+        }
+        .ok_or_else(|| InputValueError::custom("`value` must be an integer or integer string"))?;
+        let decimals = match fields.get("decimals") {
+            Some(Value::Number(decimals)) => decimals.as_u64().and_then(|d| u8::try_from(d).ok()),
+            _ => None,
+        }
+        .ok_or_else(|| InputValueError::custom("`decimals` must be an integer between 0 and 255"))?;
+        Ok(Price::new(raw, decimals))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Object(
+            [
+                (Name::new("value"), Value::String(self.value.to_string())),
+                (Name::new("decimals"), Value::Number(self.decimals.into())),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Price;
+
+    #[test]
+    fn median_of_odd_count_is_middle_price() {
+        let prices = [Price::new(300, 2), Price::new(100, 2), Price::new(200, 2)];
+        assert_eq!(Price::median(&prices), Some(Price::new(200, 2)));
+    }
+
+    #[test]
+    fn median_of_even_count_averages_toward_zero() {
+        let prices = [Price::new(100, 0), Price::new(103, 0)];
+        assert_eq!(Price::median(&prices), Some(Price::new(101, 0)));
+        let prices = [Price::new(-100, 0), Price::new(-103, 0)];
+        assert_eq!(Price::median(&prices), Some(Price::new(-101, 0)));
+    }
+
+    #[test]
+    fn median_uses_most_precise_decimals() {
+        let prices = [Price::new(1, 0), Price::new(150, 2), Price::new(2, 0)];
+        assert_eq!(Price::median(&prices), Some(Price::new(150, 2)));
+    }
+
+    #[test]
+    fn median_of_nothing_is_none() {
+        assert_eq!(Price::median(&[]), None);
+    }
+
+    #[test]
+    fn deviation_is_relative_to_reference() {
+        let reference = Price::new(10_000, 2);
+        assert_eq!(Price::new(10_100, 2).deviation_bps(reference), Some(100));
+        assert_eq!(Price::new(9_900, 2).deviation_bps(reference), Some(100));
+        assert_eq!(Price::new(101, 0).deviation_bps(reference), Some(100));
+    }
+
+    #[test]
+    fn deviation_from_zero_is_none() {
+        assert_eq!(Price::new(1, 0).deviation_bps(Price::new(0, 0)), None);
+        assert_eq!(
+            Price::new(0, 0).deviation_bps(Price::new(1, 0)),
+            Some(10_000)
+        );
+    }
+
+    #[test]
+    fn rescaling_refuses_to_lose_precision() {
+        assert_eq!(Price::new(15, 1).rescaled(3), Some(Price::new(1_500, 3)));
+        assert_eq!(Price::new(1_500, 3).rescaled(1), None);
+        assert_eq!(Price::new(i128::MAX, 0).rescaled(1), None);
+    }
+}
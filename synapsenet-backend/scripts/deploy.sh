@@ -8,40 +8,63 @@ export LINERA_KEYSTORE="/tmp/linera-net/keystore_0.json"
 export LINERA_STORAGE="rocksdb:/tmp/linera-net/client_0.db"
 
 # Build all contracts
+# The contracts share the workspace's target directory, so one invocation builds all three.
 echo "📦 Building contracts..."
-
-# Build price-feed contract
-echo "Building price-feed contract..."
-cd chains/price-feed/price-feed
-cargo build --target wasm32-unknown-unknown --release
-cd ../../..
-
-# Build identity-score contract
-echo "Building identity-score contract..."
-cd chains/identity-score/identity-score
-cargo build --target wasm32-unknown-unknown --release
-cd ../../..
-
-# Build dashboard contract
-echo "Building dashboard contract..."
-cd chains/dashboard/dashboard
-cargo build --target wasm32-unknown-unknown --release
-cd ../../..
+cargo build --profile wasm --target wasm32-unknown-unknown \
+  -p price-feed -p identity-score -p dashboard
+WASM_DIR=target/wasm32-unknown-unknown/wasm
 
 # Deploy contracts to Linera network
 echo "🚀 Deploying contracts..."
 
+# Deploy dashboard
+# DASHBOARD_MAX_EVENTS and DASHBOARD_MAX_AGE_SECS bound the events the dashboard keeps. The
+# dashboard is deployed first so that the other applications can forward events to it, which
+# it relays to DASHBOARD_CHAIN_ID, the wallet's default chain unless set. It starts
+# without sources, since the other applications' ids are not known yet; see the note below.
+echo "Deploying dashboard contract..."
+DASHBOARD_CHAIN_ID=${DASHBOARD_CHAIN_ID:-$(linera wallet show --short --owned | head -n 1)}
+DASHBOARD_APP_ID=$(linera publish-and-create \
+  $WASM_DIR/dashboard_contract.wasm $WASM_DIR/dashboard_service.wasm \
+  --json-parameters "{\"chain_id\": \"$DASHBOARD_CHAIN_ID\", \"retention\": {\"max_events\": ${DASHBOARD_MAX_EVENTS:-10000}, \"max_age_secs\": ${DASHBOARD_MAX_AGE_SECS:-null}}}" \
+  --json-argument "{\"admin\": null, \"sources\": []}")
+
 # Deploy price-feed
+# PRICE_FEED_PUBLISHERS is a JSON array of owners allowed to publish prices; the deploying
+# signer becomes the admin that can add or remove publishers later. A round's price is
+# committed once PRICE_FEED_QUORUM publishers agree within PRICE_FEED_MAX_DEVIATION_BPS.
 echo "Deploying price-feed contract..."
-linera publish-and-create chains/price-feed/price-feed/target/wasm32-unknown-unknown/release/price_feed_contract.wasm
+PRICE_FEED_APP_ID=$(linera publish-and-create \
+  $WASM_DIR/price_feed_contract.wasm $WASM_DIR/price_feed_service.wasm \
+  --json-parameters "{\"quorum\": ${PRICE_FEED_QUORUM:-1}, \"max_deviation_bps\": ${PRICE_FEED_MAX_DEVIATION_BPS:-500}, \"dashboard\": \"$DASHBOARD_APP_ID\"}" \
+  --json-argument "{\"admin\": null, \"publishers\": ${PRICE_FEED_PUBLISHERS:-[]}}" \
+  --required-application-ids "$DASHBOARD_APP_ID")
 
 # Deploy identity-score
+# IDENTITY_SCORE_MODEL is the JSON score model: a baseline plus a weight and decay per signal kind.
+# IDENTITY_SCORE_SIGNAL_ISSUERS is a JSON array of owners allowed to record signals other than
+# on-chain activity; the deploying signer becomes the admin that can set scores and add or
+# remove issuers later.
+DEFAULT_SCORE_MODEL='{"baseline": 50.0, "weights": [
+  {"kind": "OnChainActivity", "weight": 1.0, "decay": 0.1},
+  {"kind": "Attestation", "weight": 5.0, "decay": 0.0},
+  {"kind": "Penalty", "weight": -10.0, "decay": 0.05}
+]}'
+IDENTITY_SCORE_PARAMETERS=$(echo "${IDENTITY_SCORE_MODEL:-$DEFAULT_SCORE_MODEL}" \
+  | jq -c --arg dashboard "$DASHBOARD_APP_ID" '. + {dashboard: $dashboard}')
 echo "Deploying identity-score contract..."
-linera publish-and-create chains/identity-score/identity-score/target/wasm32-unknown-unknown/release/identity_score_contract.wasm
+IDENTITY_SCORE_APP_ID=$(linera publish-and-create \
+  $WASM_DIR/identity_score_contract.wasm $WASM_DIR/identity_score_service.wasm \
+  --json-parameters "$IDENTITY_SCORE_PARAMETERS" \
+  --json-argument "{\"admin\": null, \"signal_issuers\": ${IDENTITY_SCORE_SIGNAL_ISSUERS:-[]}}" \
+  --required-application-ids "$DASHBOARD_APP_ID")
 
-# Deploy dashboard
-echo "Deploying dashboard contract..."
-linera publish-and-create chains/dashboard/dashboard/target/wasm32-unknown-unknown/release/dashboard_contract.wasm
+echo "Dashboard: $DASHBOARD_APP_ID"
+echo "Price feed: $PRICE_FEED_APP_ID"
+echo "Identity score: $IDENTITY_SCORE_APP_ID"
+echo "ℹ️  Register both applications as dashboard sources with the addSource mutation,"
+echo "   otherwise the dashboard drops their events. Sources can then also be read from their"
+echo "   event streams with the subscribe mutation."
 
 echo "✅ All contracts deployed successfully!"
 echo "🌐 Linera GraphQL endpoint: http://localhost:8080/graphql"
@@ -1,35 +1,38 @@
-use linera_sdk::{
-    base::{ApplicationId, ChainId},
-    client::ChainClient,
-    graphql::GraphQLClient,
-};
+use linera_sdk::linera_base_types::{ApplicationId, ChainId};
 use serde_json::Value;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::Mutex;
 use futures_util::StreamExt;
 
+use synapsenet_common::{
+    identity_score::{self, IdentityScoreError},
+    price_feed::{self, PricePoint, RoundId, RoundMetadata, UpdateThresholds},
+};
+
 use crate::{
     error::SynapseNetError,
-    events::{Event, EventType},
-    types::SynapseNetConfig,
+    events::{Event, EventType, Subscriptions},
+    types::{price_from_payload, SynapseNetConfig},
 };
 
 pub struct SynapseNetClient {
     config: SynapseNetConfig,
-    client: ChainClient,
+    http: reqwest::Client,
+    /// Last price submitted per token, timestamped with its submission time.
+    last_submitted: Mutex<HashMap<String, PricePoint>>,
+    /// Newest oracle round submitted per token. The listener repeats a round until the oracle
+    /// answers the next one, and the contract rejects rounds that are not newer than its last.
+    submitted_rounds: Mutex<HashMap<String, RoundId>>,
 }
 
 impl SynapseNetClient {
     pub fn new(config: SynapseNetConfig) -> Self {
-        let client = ChainClient::new(
-            config.graphql_endpoint.clone(),
-            Some(config.wallet_path.clone()),
-            Some(config.keystore_path.clone()),
-            Some(config.storage_path.clone()),
-        )
-            .expect("Failed to create Linera client");
-
-        SynapseNetClient { config, client }
+        SynapseNetClient {
+            config,
+            http: reqwest::Client::new(),
+            last_submitted: Mutex::new(HashMap::new()),
+            submitted_rounds: Mutex::new(HashMap::new()),
+        }
     }
 
     pub async fn publish_event(&self, event_type: EventType, payload: Value) -> Result<(), SynapseNetError> {
@@ -39,28 +42,43 @@ impl SynapseNetClient {
         };
 
         let operation = match event_type {
-            EventType::PriceUpdate => format!(
-                "mutation {{ updatePrice(token: \"{}\", price: {}) }}",
-                payload["token"].as_str().unwrap_or_default(),
-                payload["price"].as_f64().unwrap_or_default()
-            ),
-            EventType::ScoreUpdate => format!(
-                "mutation {{ updateScore(userId: \"{}\", score: {}, reason: \"{}\") }}",
-                payload["user_id"].as_str().unwrap_or_default(),
-                payload["score"].as_f64().unwrap_or_default(),
-                payload["reason"].as_str().unwrap_or_default()
-            ),
+            EventType::PriceUpdate => update_price_mutation(&payload)?,
+            EventType::ScoreUpdate => update_score_mutation(&payload)?,
         };
 
-        self.client
-            .query_application(chain_id, application_id, operation)
-            .await
-            .map_err(|e| SynapseNetError::LineraClientError(e))?;
+        self.query_application(chain_id, application_id, operation).await?;
 
         Ok(())
     }
 
-    pub async fn listen_for_events(&mut self, subscriptions: Arc<Mutex<HashMap<EventType, Vec<Box<dyn Fn(Event) + Send>>>>>) {
+    /// Sends a GraphQL request to an application through the node service and returns the
+    /// response's `data`.
+    pub async fn query_application(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        query: String,
+    ) -> Result<Value, SynapseNetError> {
+        let url = format!(
+            "{}/chains/{chain_id}/applications/{application_id}",
+            self.config.graphql_endpoint.trim_end_matches('/')
+        );
+        let response: Value = self
+            .http
+            .post(url)
+            .json(&serde_json::json!({ "query": query }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(errors) = response.get("errors").filter(|errors| !errors.is_null()) {
+            return Err(SynapseNetError::GraphQLError(errors.to_string()));
+        }
+        Ok(response["data"].clone())
+    }
+
+    pub async fn listen_for_events(&mut self, subscriptions: Arc<Mutex<Subscriptions>>) {
         println!("🔗 Connecting to real-time blockchain data...");
         
         // Connect to WebSocket server for real Chainlink data
@@ -81,20 +99,8 @@ impl SynapseNetClient {
                                         if event_type == "price_update" {
                                             // Publish to Linera microchain and notify subscribers
                                             if let Some(payload) = data.get("data") {
-                                                let mut payload_owned = payload.clone();
-                                                // Forward to application by calling publish_event
                                                 // Note: We ignore errors here to keep stream flowing
-                                                let _ = self.client
-                                                    .query_application(
-                                                        self.config.price_feed_chain.chain_id,
-                                                        self.config.price_feed_chain.application_id,
-                                                        format!(
-                                                            "mutation {{ updatePrice(token: \"{}\", price: {}) }}",
-                                                            payload_owned["token"].as_str().unwrap_or("ETH"),
-                                                            payload_owned["price"].as_f64().unwrap_or(0.0)
-                                                        ),
-                                                    )
-                                                    .await;
+                                                let _ = self.submit_price(payload).await;
                                             }
 
                                             let event = Event {
@@ -104,7 +110,7 @@ impl SynapseNetClient {
                                                 timestamp: data.get("timestamp").and_then(|v| v.as_u64()).unwrap_or(0),
                                                 source_chain: data.get("sourceChain").and_then(|v| v.as_str()).unwrap_or("").to_string(),
                                             };
-                                            if let Ok(subs) = subscriptions.lock().await.get(&EventType::PriceUpdate) {
+                                            if let Some(subs) = subscriptions.lock().await.get(&EventType::PriceUpdate) {
                                                 for callback in subs {
                                                     callback(event.clone());
                                                 }
@@ -126,12 +132,147 @@ impl SynapseNetClient {
         }
     }
 
+    /// Submits a price payload to the price feed if it is due under the thresholds the price
+    /// feed applies to its token, skipping oracle rounds that were already submitted.
+    async fn submit_price(&self, payload: &Value) -> Result<(), SynapseNetError> {
+        let token = payload["token"].as_str().unwrap_or_default().to_string();
+        let price = price_from_payload(payload)?;
+        let now = chrono::Utc::now().timestamp_micros() as u64;
+        let round_id = parse_round_id(&payload["roundId"])?;
+        if let Some(round_id) = round_id {
+            let submitted_rounds = self.submitted_rounds.lock().await;
+            if submitted_rounds
+                .get(&token)
+                .is_some_and(|submitted| round_id <= *submitted)
+            {
+                return Err(SynapseNetError::Other(format!(
+                    "Round {round_id} of {token} was already submitted"
+                )));
+            }
+        }
+        let last_submitted = self.last_submitted.lock().await.get(&token).cloned();
+        if let Some(last_submitted) = last_submitted {
+            let thresholds = self.update_thresholds(&token).await?;
+            if !thresholds.is_none_or(|thresholds| thresholds.is_due(&last_submitted, price, now)) {
+                return Err(SynapseNetError::Other(format!(
+                    "Price of {token} is not due yet"
+                )));
+            }
+        }
+
+        let operation = update_price_mutation(payload)?;
+        self.query_application(
+            self.config.price_feed_chain.chain_id,
+            self.config.price_feed_chain.application_id,
+            operation,
+        )
+        .await?;
+        if let Some(round_id) = round_id {
+            self.submitted_rounds
+                .lock()
+                .await
+                .insert(token.clone(), round_id);
+        }
+        self.last_submitted.lock().await.insert(
+            token,
+            PricePoint {
+                price,
+                round: None,
+                timestamp: now,
+            },
+        );
+        Ok(())
+    }
+
+    /// Reads the thresholds the price feed applies to `token`, if any, so that prices it would
+    /// coalesce are not submitted.
+    async fn update_thresholds(
+        &self,
+        token: &str,
+    ) -> Result<Option<UpdateThresholds>, SynapseNetError> {
+        // A JSON string literal is also a GraphQL one.
+        let token = serde_json::to_string(token)?;
+        let data = self
+            .query_application(
+                self.config.price_feed_chain.chain_id,
+                self.config.price_feed_chain.application_id,
+                format!(
+                    "query {{ updateThresholds(token: {token}) {{ \
+                        deviation_bps: deviationBps heartbeat_secs: heartbeatSecs }} }}"
+                ),
+            )
+            .await?;
+        Ok(serde_json::from_value(data["updateThresholds"].clone())?)
+    }
+
     pub async fn refresh_config(&mut self) -> Result<(), SynapseNetError> {
-        let response = reqwest::get("http://localhost:8091/config").await
-            .map_err(|e| SynapseNetError::from(e))?;
-        let config: SynapseNetConfig = response.json().await
-            .map_err(|e| SynapseNetError::from(e))?;
-        *self = SynapseNetClient::new(config.clone());
+        let response = reqwest::get("http://localhost:8091/config").await?;
+        let config: SynapseNetConfig = response.json().await?;
+        *self = SynapseNetClient::new(config);
         Ok(())
     }
 }
+
+/// Builds the price-feed mutation for a price payload, using `updateRound` when the payload
+/// carries Chainlink round metadata so that the contract can reject stale rounds.
+fn update_price_mutation(payload: &Value) -> Result<String, SynapseNetError> {
+    let token = payload["token"].as_str().unwrap_or_default().to_string();
+    let price = price_from_payload(payload)?;
+
+    let Some(round_id) = parse_round_id(&payload["roundId"])? else {
+        return Ok(price_feed::Operation::UpdatePrice { token, price }
+            .to_mutation()
+            .expect("UpdatePrice is a mutation"));
+    };
+    let answered_in_round = parse_round_id(&payload["answeredInRound"])?.unwrap_or(round_id);
+    let round = RoundMetadata {
+        round_id,
+        answered_in_round,
+        started_at: payload["startedAt"].as_u64().unwrap_or_default(),
+        updated_at: payload["updatedAt"].as_u64().unwrap_or_default(),
+        network: payload["network"].as_str().unwrap_or_default().to_string(),
+    };
+    Ok(price_feed::Operation::UpdateRound {
+        token,
+        price,
+        round,
+    }
+    .to_mutation()
+    .expect("UpdateRound is a mutation"))
+}
+
+/// Builds the identity-score mutation for a score payload, rejecting locally what the contract
+/// would reject regardless of its configured range. The contract only accepts it from the
+/// application's admin.
+fn update_score_mutation(payload: &Value) -> Result<String, SynapseNetError> {
+    let user_id = payload["user_id"].as_str().unwrap_or_default().to_string();
+    if user_id.is_empty() {
+        return Err(IdentityScoreError::EmptyUserId.into());
+    }
+    let score = payload["score"]
+        .as_f64()
+        .filter(|score| score.is_finite())
+        .ok_or_else(|| IdentityScoreError::NonFinite {
+            field: "score".to_string(),
+        })?;
+
+    Ok(identity_score::Operation::UpdateScore {
+        user_id,
+        score,
+        reason: payload["reason"].as_str().unwrap_or_default().to_string(),
+    }
+    .to_mutation()
+    .expect("UpdateScore is a mutation"))
+}
+
+/// Reads a round id, which may have been sent as a string to survive JSON number precision.
+fn parse_round_id(value: &Value) -> Result<Option<RoundId>, SynapseNetError> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        _ => return Ok(None),
+    };
+    text.parse()
+        .map(|id| Some(RoundId(id)))
+        .map_err(|e| SynapseNetError::Other(format!("Invalid round id {text}: {e}")))
+}
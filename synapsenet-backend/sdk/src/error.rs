@@ -1,9 +1,8 @@
+use synapsenet_common::identity_score::IdentityScoreError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum SynapseNetError {
-    #[error("Linera client error: {0}")]
-    LineraClientError(#[from] linera_sdk::client::ChainClientError),
     #[error("GraphQL error: {0}")]
     GraphQLError(String),
     #[error("Serialization error: {0}")]
@@ -12,6 +11,8 @@ pub enum SynapseNetError {
     HttpError(#[from] reqwest::Error),
     #[error("Invalid event type: {0}")]
     InvalidEventType(String),
+    #[error("Score rejected: {0}")]
+    ScoreRejected(#[from] IdentityScoreError),
     #[error("Other error: {0}")]
     Other(String),
 }
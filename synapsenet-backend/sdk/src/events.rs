@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -9,6 +10,9 @@ pub enum EventType {
     // Add other event types as needed
 }
 
+/// Callbacks registered for each event type.
+pub type Subscriptions = HashMap<EventType, Vec<Box<dyn Fn(Event) + Send>>>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     pub id: String,
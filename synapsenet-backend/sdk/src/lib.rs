@@ -1,17 +1,6 @@
-use async_trait::async_trait;
-use linera_sdk::{
-    base::{Amount, ApplicationId, ChainId, Owner},
-    client::ChainClient,
-    graphql::GraphQLClient,
-    views::View,
-};
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::Mutex;
-use uuid::Uuid;
-use std::str::FromStr;
-use std::env;
 
 pub mod error;
 pub mod events;
@@ -19,26 +8,30 @@ pub mod types;
 pub mod client;
 
 use error::SynapseNetError;
-use events::{Event, EventType};
-use types::{ChainConfig, SynapseNetConfig};
+pub use events::{Event, EventType, Subscriptions};
+use types::SynapseNetConfig;
 use client::SynapseNetClient;
 
 pub struct SynapseNet {
     client: Arc<Mutex<SynapseNetClient>>,
-    config: SynapseNetConfig,
-    subscriptions: Arc<Mutex<HashMap<EventType, Vec<Box<dyn Fn(Event) + Send>>>>>,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+}
+
+impl Default for SynapseNet {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SynapseNet {
     pub fn new() -> Self {
         let config = SynapseNetConfig::from_env().expect("Failed to build SynapseNet configuration");
 
-        let client = Arc::new(Mutex::new(SynapseNetClient::new(config.clone())));
+        let client = Arc::new(Mutex::new(SynapseNetClient::new(config)));
         let subscriptions = Arc::new(Mutex::new(HashMap::new()));
 
         SynapseNet {
             client,
-            config,
             subscriptions,
         }
     }
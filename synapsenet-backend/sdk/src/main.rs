@@ -1,6 +1,4 @@
 use synapsenet_sdk::{SynapseNet, EventType};
-use serde_json::json;
-use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -10,7 +8,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let synapsenet = SynapseNet::new();
 
     // Subscribe to price updates
-    let _price_subscription = synapsenet.subscribe(EventType::PriceUpdate, |event| {
+    synapsenet.subscribe(EventType::PriceUpdate, |event| {
         println!("💰 Price Update: Token: {}, Price: ${:.2}",
             event.payload["token"].as_str().unwrap_or("Unknown"),
             event.payload["price"].as_f64().unwrap_or(0.0)
@@ -18,7 +16,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }).await?;
 
     // Subscribe to score updates
-    let _score_subscription = synapsenet.subscribe(EventType::ScoreUpdate, |event| {
+    synapsenet.subscribe(EventType::ScoreUpdate, |event| {
         println!("👤 Score Update: User: {}, Score: {:.1}",
             event.payload["user_id"].as_str().unwrap_or("Unknown"),
             event.payload["score"].as_f64().unwrap_or(0.0)
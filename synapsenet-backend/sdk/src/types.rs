@@ -1,6 +1,10 @@
-use linera_sdk::base::{ApplicationId, ChainId};
+use linera_sdk::linera_base_types::{ApplicationId, ChainId};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::str::FromStr;
+pub use synapsenet_common::Price;
+
+use crate::error::SynapseNetError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainConfig {
@@ -13,10 +17,9 @@ pub struct SynapseNetConfig {
     pub price_feed_chain: ChainConfig,
     pub identity_score_chain: ChainConfig,
     pub dashboard_chain: ChainConfig,
+    /// URL of the Linera node service. Applications are queried at
+    /// `<graphql_endpoint>/chains/<chain id>/applications/<application id>`.
     pub graphql_endpoint: String,
-    pub wallet_path: String,
-    pub keystore_path: String,
-    pub storage_path: String,
 }
 
 impl SynapseNetConfig {
@@ -26,11 +29,6 @@ impl SynapseNetConfig {
         const DEFAULT_IDENTITY_SCORE_APP_ID: &str = "3966161f27823fe841186b7d00534d05551ee8b55759d877a758c527620aa5f6";
         const DEFAULT_DASHBOARD_APP_ID: &str = "0fa26c20ef1eb8d10043255bd92e03c5396ff478d3f188644530bce5368741ad";
 
-        const DEFAULT_CHAIN_ID: &str = "617b38dd13174e227eec44357524c7c692361ce134e60de753485d2f692f6d56";
-        const DEFAULT_PRICE_FEED_APP_ID: &str = "6694d606dbbcc922ac825fb846d3ea84cb62587cda95ba3bcac9af40c3cae920";
-        const DEFAULT_IDENTITY_SCORE_APP_ID: &str = "3966161f27823fe841186b7d00534d05551ee8b55759d877a758c527620aa5f6";
-        const DEFAULT_DASHBOARD_APP_ID: &str = "0fa26c20ef1eb8d10043255bd92e03c5396ff478d3f188644530bce5368741ad";
-
         let parse_chain = |env_key: &str| -> Result<ChainId, anyhow::Error> {
             let value = std::env::var(env_key).unwrap_or_else(|_| DEFAULT_CHAIN_ID.to_string());
             ChainId::from_str(&value).map_err(|e| anyhow::anyhow!("Invalid chain id {env_key}: {e}"))
@@ -57,22 +55,52 @@ impl SynapseNetConfig {
         };
 
         let graphql_endpoint = std::env::var("LINERA_GRAPHQL_ENDPOINT")
-            .unwrap_or_else(|_| "http://localhost:8080/graphql".to_string());
-        let wallet_path = std::env::var("LINERA_WALLET_PATH")
-            .unwrap_or_else(|_| "/tmp/linera-net/wallet_0.json".to_string());
-        let keystore_path = std::env::var("LINERA_KEYSTORE_PATH")
-            .unwrap_or_else(|_| "/tmp/linera-net/keystore_0.json".to_string());
-        let storage_path = std::env::var("LINERA_STORAGE_PATH")
-            .unwrap_or_else(|_| "rocksdb:/tmp/linera-net/client_0.db".to_string());
+            .unwrap_or_else(|_| "http://localhost:8080".to_string());
 
         Ok(SynapseNetConfig {
             price_feed_chain,
             identity_score_chain,
             dashboard_chain,
             graphql_endpoint,
-            wallet_path,
-            keystore_path,
-            storage_path,
         })
     }
 }
+
+/// Decimals used by Chainlink USD feeds.
+pub const CHAINLINK_USD_DECIMALS: u8 = 8;
+
+/// Reads the price of an event payload, preferring the raw oracle `answer` and `decimals` and
+/// falling back to the decimal `price` field.
+pub fn price_from_payload(payload: &Value) -> Result<Price, SynapseNetError> {
+    let decimals = payload["decimals"]
+        .as_u64()
+        .map(u8::try_from)
+        .transpose()
+        .map_err(|_| SynapseNetError::Other(format!("Invalid decimals: {}", payload["decimals"])))?
+        .unwrap_or(CHAINLINK_USD_DECIMALS);
+
+    let answer = match &payload["answer"] {
+        Value::String(answer) => Some(answer.clone()),
+        Value::Number(answer) => Some(answer.to_string()),
+        _ => None,
+    };
+    if let Some(answer) = answer {
+        let value = answer
+            .parse::<i128>()
+            .map_err(|e| SynapseNetError::Other(format!("Invalid answer {answer}: {e}")))?;
+        return Ok(Price::new(value, decimals));
+    }
+
+    let text = match &payload["price"] {
+        Value::String(price) => price.clone(),
+        Value::Number(price) => price.to_string(),
+        other => {
+            return Err(SynapseNetError::Other(format!(
+                "Missing price in payload: {other}"
+            )))
+        }
+    };
+    Price::from_decimal_str(&text, decimals).ok_or_else(|| {
+        SynapseNetError::Other(format!("Invalid price {text} with {decimals} decimals"))
+    })
+}
@@ -11,6 +11,7 @@ dotenv.config();
 const CHAINLINK_CONTRACT = process.env.CHAINLINK_CONTRACT || "0x9b8e6d8b2417116f4ff9bc4e9b9f91a8a7d2f8e5";
 const POLYGON_AMOY_RPC = process.env.POLYGON_AMOY_RPC || "https://rpc-amoy.polygon.technology";
 const SEPOLIA_RPC = process.env.SEPOLIA_RPC || "https://ethereum-sepolia.publicnode.com";
+const CHAINLINK_DECIMALS = 8; // ETH/USD feeds report answers with 8 decimals
 
 // OPTIMIZED: Faster RPC endpoints (uncomment and add your keys)
 // const POLYGON_AMOY_RPC = "https://polygon-amoy.infura.io/v3/YOUR_INFURA_KEY";
@@ -33,6 +34,16 @@ const CHAINLINK_ABI = [
   }
 ];
 
+// Round ids are uint80, so they are sent as strings to survive JSON number precision
+function roundMetadata(roundData) {
+  return {
+    roundId: roundData.roundId.toString(),
+    answeredInRound: roundData.answeredInRound.toString(),
+    startedAt: Number(roundData.startedAt),
+    updatedAt: Number(roundData.updatedAt),
+  };
+}
+
 class OptimizedChainlinkListener {
   constructor() {
     // OPTIMIZED: Connection pooling and reuse
@@ -54,6 +65,8 @@ class OptimizedChainlinkListener {
     
     // OPTIMIZED: Caching
     this.lastSuccessfulPrice = null;
+    this.lastSuccessfulAnswer = null;
+    this.lastSuccessfulRound = null;
     this.lastSuccessfulTime = 0;
     this.cacheTimeout = 5000; // 5 seconds cache (more frequent updates)
     
@@ -113,11 +126,15 @@ class OptimizedChainlinkListener {
         const startTime = Date.now();
         let usingFallback = false;
         let price;
+        let answer = null; // Raw int256 answer, only set when read from an oracle
+        let round = null; // latestRoundData metadata for that answer
         
         // OPTIMIZED: Check cache first
         const now = Date.now();
         if (this.lastSuccessfulPrice && (now - this.lastSuccessfulTime) < this.cacheTimeout) {
           price = this.lastSuccessfulPrice;
+          answer = this.lastSuccessfulAnswer;
+          round = this.lastSuccessfulRound;
           console.log(`💰 Using cached price: $${price.toFixed(2)} (${now - this.lastSuccessfulTime}ms old)`);
         } else {
           try {
@@ -125,9 +142,13 @@ class OptimizedChainlinkListener {
             // Direct call to latestRoundData
             const roundData = await this.contract.latestRoundData();
             price = Number(roundData.answer) / 1e8; // Chainlink uses 8 decimals
+            answer = roundData.answer.toString();
+            round = roundMetadata(roundData);
             
             // Update cache
             this.lastSuccessfulPrice = price;
+            this.lastSuccessfulAnswer = answer;
+            this.lastSuccessfulRound = round;
             this.lastSuccessfulTime = now;
             
           } catch (e) {
@@ -136,10 +157,14 @@ class OptimizedChainlinkListener {
               const fbContract = new ethers.Contract(this.fallback.address, CHAINLINK_ABI, this.fallback.provider);
               const rd = await fbContract.latestRoundData();
               price = Number(rd.answer) / 1e8;
+              answer = rd.answer.toString();
+              round = roundMetadata(rd);
               usingFallback = true;
               
               // Update cache
               this.lastSuccessfulPrice = price;
+              this.lastSuccessfulAnswer = answer;
+              this.lastSuccessfulRound = round;
               this.lastSuccessfulTime = now;
               
             } catch (fallbackError) {
@@ -170,6 +195,8 @@ class OptimizedChainlinkListener {
           data: {
             token: "ETH",
             price: Number(price.toFixed(2)),
+            // Exact fixed-point value for on-chain publishing
+            ...(answer !== null && { answer, decimals: CHAINLINK_DECIMALS, ...round }),
             source: "Chainlink Oracle",
             network: usingFallback ? this.fallback.label : "Polygon Amoy"
           },